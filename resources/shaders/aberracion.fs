@@ -0,0 +1,19 @@
+#version 330 core
+
+// Aberración cromática: rojo y azul se separan hacia fuera y hacia dentro, más cuanto más lejos del centro
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform float strength;       // Separación en el borde, en unidades de UV
+
+out vec4 finalColor;
+
+void main() {
+    vec2 offset = (fragTexCoord - 0.5) * 2.0 * strength;
+    float r = texture(texture0, fragTexCoord + offset).r;
+    float g = texture(texture0, fragTexCoord).g;
+    float b = texture(texture0, fragTexCoord - offset).b;
+    finalColor = vec4(r, g, b, 1.0);
+}
@@ -0,0 +1,17 @@
+#version 330 core
+
+// Bloom, paso 3: suma el brillo desenfocado sobre la imagen HDR
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;     // Imagen HDR
+uniform sampler2D bloomTexture; // Resultado del desenfoque
+uniform float intensity;
+
+out vec4 finalColor;
+
+void main() {
+    vec3 color = texture(texture0, fragTexCoord).rgb + texture(bloomTexture, fragTexCoord).rgb * intensity;
+    finalColor = vec4(color, 1.0);
+}
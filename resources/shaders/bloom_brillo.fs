@@ -0,0 +1,21 @@
+#version 330 core
+
+// Bloom, paso 1: deja sólo la luz por encima del umbral (con una rodilla suave para que no haya corte)
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;   // Framebuffer HDR en lineal
+uniform float threshold;
+
+out vec4 finalColor;
+
+void main() {
+    vec3 color = texture(texture0, fragTexCoord).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float knee = threshold * 0.5;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-4);
+    float contribution = max(soft, brightness - threshold) / max(brightness, 1e-4);
+    finalColor = vec4(color * contribution, 1.0);
+}
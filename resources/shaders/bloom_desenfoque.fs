@@ -0,0 +1,27 @@
+#version 330 core
+
+// Bloom, paso 2: desenfoque gaussiano separable (una pasada horizontal y otra vertical)
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec2 resolution;      // Tamaño del render target de media resolución
+uniform vec2 direction;       // (1, 0) o (0, 1)
+uniform float blurRadius;
+
+out vec4 finalColor;
+
+// Gaussiana de 9 muestras aprovechando el filtro bilineal (5 lecturas)
+const float offsets[3] = float[](0.0, 1.3846153846, 3.2307692308);
+const float weights[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main() {
+    vec2 stepUV = direction * blurRadius / resolution;
+    vec3 color = texture(texture0, fragTexCoord).rgb * weights[0];
+    for (int i = 1; i < 3; i++) {
+        color += texture(texture0, fragTexCoord + stepUV * offsets[i]).rgb * weights[i];
+        color += texture(texture0, fragTexCoord - stepUV * offsets[i]).rgb * weights[i];
+    }
+    finalColor = vec4(color, 1.0);
+}
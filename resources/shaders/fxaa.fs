@@ -0,0 +1,43 @@
+#version 330 core
+
+// FXAA sobre la imagen final en sRGB: busca los bordes por luminancia y promedia a lo largo de ellos
+
+#define FXAA_REDUCE_MIN (1.0 / 128.0)
+#define FXAA_REDUCE_MUL (1.0 / 8.0)
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec2 resolution;
+uniform float spanMax;        // Largo máximo del borde en píxeles
+
+out vec4 finalColor;
+
+void main() {
+    vec2 texel = 1.0 / resolution;
+    vec3 luma = vec3(0.299, 0.587, 0.114);
+    vec3 rgbM = texture(texture0, fragTexCoord).rgb;
+    float lumaNW = dot(texture(texture0, fragTexCoord + vec2(-1.0, -1.0) * texel).rgb, luma);
+    float lumaNE = dot(texture(texture0, fragTexCoord + vec2(1.0, -1.0) * texel).rgb, luma);
+    float lumaSW = dot(texture(texture0, fragTexCoord + vec2(-1.0, 1.0) * texel).rgb, luma);
+    float lumaSE = dot(texture(texture0, fragTexCoord + vec2(1.0, 1.0) * texel).rgb, luma);
+    float lumaM = dot(rgbM, luma);
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    // Dirección perpendicular al gradiente, es decir, a lo largo del borde
+    vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+    dir = clamp(dir * rcpDirMin, vec2(-spanMax), vec2(spanMax)) * texel;
+
+    vec3 rgbA = 0.5 * (texture(texture0, fragTexCoord + dir * (1.0 / 3.0 - 0.5)).rgb
+                     + texture(texture0, fragTexCoord + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgbB = rgbA * 0.5 + 0.25 * (texture(texture0, fragTexCoord - dir * 0.5).rgb
+                                   + texture(texture0, fragTexCoord + dir * 0.5).rgb);
+    float lumaB = dot(rgbB, luma);
+    // Si el promedio largo se sale del rango local, cruzó otro borde: se usa el corto
+    vec3 color = (lumaB < lumaMin || lumaB > lumaMax) ? rgbA : rgbB;
+    finalColor = vec4(color, 1.0);
+}
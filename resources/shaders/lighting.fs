@@ -1,34 +1,225 @@
 #version 330 core
 
+#define MAX_LIGHTS 4
+#define PI 3.14159265
+
+// Mismos valores que LightKind::shader_id
+#define LIGHT_POINT 0
+#define LIGHT_DIRECTIONAL 1
+#define LIGHT_SPOT 2
+
 in vec3 fragPos;
 in vec3 normal;
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;   // Textura activa (rlSetTexture)
 
-uniform vec3 lightPos;        // Posición de la luz (mundo)
-uniform vec3 lightColor;      // Color de la luz
+uniform int lightCount;                 // Luces activas (<= MAX_LIGHTS)
+uniform int lightType[MAX_LIGHTS];      // LIGHT_POINT / LIGHT_DIRECTIONAL / LIGHT_SPOT
+uniform vec3 lightPos[MAX_LIGHTS];      // Posición de la luz (mundo)
+uniform vec3 lightDir[MAX_LIGHTS];      // Hacia dónde apunta (direccional y foco)
+uniform vec3 lightColor[MAX_LIGHTS];    // Color de la luz
+uniform vec3 lightAtten[MAX_LIGHTS];    // Atenuación constante, lineal, cuadrática
+uniform vec2 lightCone[MAX_LIGHTS];     // Coseno del ángulo interior y exterior (foco)
 uniform vec3 viewPos;         // Posición de la cámara (mundo)
+// Material del objeto (ver material.rs)
 uniform vec3 objectColor;     // Color base del objeto
 uniform float ambientStrength; // Fuerza de luz ambiente
+uniform float diffuseStrength; // Fuerza del difuso
 uniform float specularStrength; // Fuerza del especular
 uniform float shininess;       // Brillo del especular
+uniform vec3 emissiveColor;    // Luz propia
+
+// Mapas de normales y de altura (relieve.rs hace lo mismo en el trazador)
+uniform sampler2D normalMap;   // Espacio tangente, verde hacia arriba en la imagen (OpenGL)
+uniform sampler2D heightMap;   // Blanco = alto
+uniform int useNormalMap;
+uniform int useHeightMap;
+uniform float normalStrength;
+uniform float parallaxScale;   // Profundidad en unidades de UV
+
+// Entorno (ver entorno.rs): mapa equirectangular lineal con mipmaps e irradiancia en armónicos esféricos
+uniform int useEnvironment;        // 1 = iluminación basada en imagen
+uniform sampler2D environmentMap;
+uniform vec3 environmentSH[9];     // Irradiancia ya convolucionada con el coseno
+uniform float environmentIntensity;
+uniform float environmentRotation; // Radianes alrededor de Y
+uniform float environmentMaxLod;   // Mip que corresponde a roughness 1
+
+// Un shadow map por luz, renderizado desde cada luz
+uniform mat4 lightVP[MAX_LIGHTS];
+uniform sampler2D shadowMap0;
+uniform sampler2D shadowMap1;
+uniform sampler2D shadowMap2;
+uniform sampler2D shadowMap3;
+uniform int shadowCount;      // Luces con shadow map (0 = sin sombras)
+uniform int shadowMapResolution;
+uniform int pcfRadius;
 
 out vec4 finalColor;
 
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 rotateEnvironment(vec3 d) {
+    float s = sin(-environmentRotation);
+    float c = cos(-environmentRotation);
+    return vec3(d.x * c + d.z * s, d.y, -d.x * s + d.z * c);
+}
+
+vec2 equirectUV(vec3 d) {
+    return vec2(0.5 + atan(d.z, d.x) / (2.0 * PI), acos(clamp(d.y, -1.0, 1.0)) / PI);
+}
+
+// Irradiancia difusa (mismo cálculo que Entorno::irradiance)
+vec3 irradianceSH(vec3 n) {
+    vec3 d = rotateEnvironment(n);
+    vec3 e = environmentSH[0] * 0.282095
+        + environmentSH[1] * 0.488603 * d.y
+        + environmentSH[2] * 0.488603 * d.z
+        + environmentSH[3] * 0.488603 * d.x
+        + environmentSH[4] * 1.092548 * d.x * d.y
+        + environmentSH[5] * 1.092548 * d.y * d.z
+        + environmentSH[6] * 0.315392 * (3.0 * d.z * d.z - 1.0)
+        + environmentSH[7] * 1.092548 * d.x * d.z
+        + environmentSH[8] * 0.546274 * (d.x * d.x - d.y * d.y);
+    return max(e, vec3(0.0)) * environmentIntensity;
+}
+
+// Reflejo prefiltrado: más rugoso, mip más borroso (mismo criterio que Entorno::sample_rough)
+vec3 environmentSpecular(vec3 R, float r) {
+    return textureLod(environmentMap, equirectUV(rotateEnvironment(R)), r * environmentMaxLod).rgb * environmentIntensity;
+}
+
+// Base tangente (dP/du, dP/dv, N) a partir de las derivadas de la posición y la UV:
+// no hace falta enviar tangentes por vértice
+mat3 cotangentFrame(vec3 N, vec3 p, vec2 uv) {
+    vec3 dp1 = dFdx(p);
+    vec3 dp2 = dFdy(p);
+    vec2 duv1 = dFdx(uv);
+    vec2 duv2 = dFdy(uv);
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+    return mat3(T * inversesqrt(max(dot(T, T), 1e-20)), B * inversesqrt(max(dot(B, B), 1e-20)), N);
+}
+
+// Parallax occlusion mapping con la vista en espacio tangente (mismo algoritmo que relieve::parallax_uv)
+vec2 parallaxUV(vec2 uv, vec3 viewTS) {
+    if (viewTS.z <= 1e-4) return uv;
+    float layers = mix(32.0, 8.0, min(viewTS.z, 1.0));
+    float layerDepth = 1.0 / layers;
+    vec2 delta = viewTS.xy / viewTS.z * parallaxScale / layers;
+
+    vec2 currentUV = uv;
+    float currentDepth = 0.0;
+    float mapDepth = 1.0 - textureLod(heightMap, currentUV, 0.0).r;
+    for (int i = 0; i < 32 && currentDepth < mapDepth; i++) {
+        currentUV -= delta;
+        mapDepth = 1.0 - textureLod(heightMap, currentUV, 0.0).r;
+        currentDepth += layerDepth;
+    }
+
+    // Interpolar entre la última capa sobre la superficie y la primera por debajo
+    vec2 previousUV = currentUV + delta;
+    float after = mapDepth - currentDepth;
+    float before = (1.0 - textureLod(heightMap, previousUV, 0.0).r) - (currentDepth - layerDepth);
+    float weight = abs(after - before) > 1e-6 ? after / (after - before) : 0.0;
+    return mix(currentUV, previousUV, weight);
+}
+
+// GLSL 330 no permite indexar samplers con un índice no constante
+float sampleShadowMap(int i, vec2 uv) {
+    if (i == 0) return texture(shadowMap0, uv).r;
+    if (i == 1) return texture(shadowMap1, uv).r;
+    if (i == 2) return texture(shadowMap2, uv).r;
+    return texture(shadowMap3, uv).r;
+}
+
+// 1.0 = iluminado, 0.0 = en sombra (promedio PCF)
+float shadowFactor(int i, vec3 N, vec3 L) {
+    if (i >= shadowCount) return 1.0;
+
+    vec4 lightSpace = lightVP[i] * vec4(fragPos, 1.0);
+    vec3 proj = lightSpace.xyz / lightSpace.w * 0.5 + 0.5;
+    if (proj.z > 1.0 || proj.x < 0.0 || proj.x > 1.0 || proj.y < 0.0 || proj.y > 1.0) return 1.0;
+
+    // Bias según la inclinación para evitar shadow acne
+    float bias = max(0.0005 * (1.0 - dot(N, L)), 0.00005);
+    float texel = 1.0 / float(shadowMapResolution);
+
+    float lit = 0.0;
+    int samples = 0;
+    for (int x = -pcfRadius; x <= pcfRadius; x++) {
+        for (int y = -pcfRadius; y <= pcfRadius; y++) {
+            float depth = sampleShadowMap(i, proj.xy + vec2(x, y) * texel);
+            lit += (proj.z - bias > depth) ? 0.0 : 1.0;
+            samples++;
+        }
+    }
+    return lit / float(samples);
+}
+
 void main() {
     vec3 N = normalize(normal);
-    vec3 L = normalize(lightPos - fragPos);
+    vec3 V = normalize(viewPos - fragPos);
 
-    // Difuso
-    float diff = max(dot(N, L), 0.0);
+    // Relieve: la UV se desplaza con el mapa de alturas y la normal sale del mapa de normales
+    vec2 uv = fragTexCoord;
+    if (useNormalMap == 1 || useHeightMap == 1) {
+        mat3 TBN = cotangentFrame(N, fragPos, fragTexCoord);
+        if (useHeightMap == 1) uv = parallaxUV(uv, transpose(TBN) * V);
+        if (useNormalMap == 1) {
+            vec3 n = texture(normalMap, uv).rgb * 2.0 - 1.0;
+            N = normalize(TBN[0] * (n.x * normalStrength) - TBN[1] * (n.y * normalStrength) + N * n.z);
+        }
+    }
 
-    // Especular (Phong)
-    vec3 V = normalize(viewPos - fragPos);
-    vec3 R = reflect(-L, N);
-    float spec = pow(max(dot(V, R), 0.0), shininess);
+    vec3 diffuse = vec3(0.0);
+    vec3 specular = vec3(0.0);
+    for (int i = 0; i < lightCount; i++) {
+        vec3 L;
+        float attenuation = 1.0;
+        if (lightType[i] == LIGHT_DIRECTIONAL) {
+            L = normalize(-lightDir[i]);
+        } else {
+            vec3 toLight = lightPos[i] - fragPos;
+            float d = length(toLight);
+            L = toLight / d;
+            attenuation = 1.0 / (lightAtten[i].x + lightAtten[i].y * d + lightAtten[i].z * d * d);
+            if (lightType[i] == LIGHT_SPOT) {
+                float cosTheta = dot(-L, normalize(lightDir[i]));
+                attenuation *= smoothstep(lightCone[i].y, lightCone[i].x, cosTheta);
+            }
+        }
+
+        // Difuso
+        float diff = max(dot(N, L), 0.0);
+
+        // Especular (Phong)
+        vec3 R = reflect(-L, N);
+        float spec = pow(max(dot(V, R), 0.0), shininess);
+
+        float shadow = shadowFactor(i, N, L) * attenuation;
+        diffuse += shadow * diffuseStrength * diff * lightColor[i];
+        specular += shadow * specularStrength * spec * lightColor[i];
+    }
+
+    // La luz ambiente se toma una sola vez, con el color de la primera luz
+    vec3 ambient = lightCount > 0 ? ambientStrength * lightColor[0] : vec3(ambientStrength);
+    if (useEnvironment == 1) {
+        // Con entorno: ambiente difuso de la irradiancia y reflejo con el brillo equivalente a shininess
+        ambient = irradianceSH(N) / PI;
+        specular += specularStrength * environmentSpecular(reflect(-V, N), sqrt(2.0 / (shininess + 2.0)));
+    }
 
-    vec3 ambient = ambientStrength * lightColor;
-    vec3 diffuse = diff * lightColor;
-    vec3 specular = specularStrength * spec * lightColor;
+    vec4 texel = texture(texture0, uv);
+    vec3 albedo = objectColor * srgbToLinear(texel.rgb) * srgbToLinear(fragColor.rgb);
 
-    vec3 color = (ambient + diffuse + specular) * objectColor;
-    finalColor = vec4(color, 1.0);
+    // En lineal y sin recortar; tonemap.fs aplica la exposición y pasa a sRGB
+    vec3 color = (ambient + diffuse) * albedo + specular + emissiveColor;
+    finalColor = vec4(color, texel.a * fragColor.a);
 }
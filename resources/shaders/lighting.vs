@@ -2,13 +2,17 @@
 
 // Raylib attribute names
 layout (location = 0) in vec3 vertexPosition;
+layout (location = 1) in vec2 vertexTexCoord;
 layout (location = 2) in vec3 vertexNormal;
+layout (location = 3) in vec4 vertexColor;
 
 uniform mat4 mvp;
 uniform mat4 matModel;
 
 out vec3 fragPos;
 out vec3 normal;
+out vec2 fragTexCoord;
+out vec4 fragColor;
 
 void main() {
     vec4 worldPos = matModel * vec4(vertexPosition, 1.0);
@@ -17,5 +21,8 @@ void main() {
     mat3 normalMatrix = mat3(transpose(inverse(matModel)));
     normal = normalize(normalMatrix * vertexNormal);
 
+    fragTexCoord = vertexTexCoord;
+    fragColor = vertexColor;
+
     gl_Position = mvp * vec4(vertexPosition, 1.0);
 }
@@ -0,0 +1,30 @@
+#version 330 core
+
+// Corrección de color con una LUT 3D guardada como tira de N cortes de NxN (azul = corte,
+// rojo = x dentro del corte, verde = y). Se interpola entre los dos cortes más cercanos
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;   // Imagen final en sRGB
+uniform sampler2D lutTexture;
+uniform float lutSize;        // N (alto de la imagen)
+uniform float strength;       // 0 = original, 1 = LUT completa
+
+out vec4 finalColor;
+
+vec3 lookup(vec3 c) {
+    float n = lutSize;
+    float blue = c.b * (n - 1.0);
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, n - 1.0);
+    vec2 uv = vec2((c.r * (n - 1.0) + 0.5) / (n * n), (c.g * (n - 1.0) + 0.5) / n);
+    vec3 a = texture(lutTexture, uv + vec2(slice0 / n, 0.0)).rgb;
+    vec3 b = texture(lutTexture, uv + vec2(slice1 / n, 0.0)).rgb;
+    return mix(a, b, blue - slice0);
+}
+
+void main() {
+    vec3 color = clamp(texture(texture0, fragTexCoord).rgb, 0.0, 1.0);
+    finalColor = vec4(mix(color, lookup(color), strength), 1.0);
+}
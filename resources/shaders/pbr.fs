@@ -0,0 +1,264 @@
+#version 330 core
+
+// Cook-Torrance GGX (metallic / roughness) en espacio lineal; mismo modelo que src/pbr.rs.
+// Usa lighting.vs y los mismos uniforms de luces y sombras que lighting.fs
+
+#define MAX_LIGHTS 4
+#define PI 3.14159265
+
+// Mismos valores que LightKind::shader_id
+#define LIGHT_POINT 0
+#define LIGHT_DIRECTIONAL 1
+#define LIGHT_SPOT 2
+
+in vec3 fragPos;
+in vec3 normal;
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;   // Textura de albedo (sRGB)
+
+uniform int lightCount;
+uniform int lightType[MAX_LIGHTS];
+uniform vec3 lightPos[MAX_LIGHTS];
+uniform vec3 lightDir[MAX_LIGHTS];
+uniform vec3 lightColor[MAX_LIGHTS];    // Irradiancia en incidencia normal (lineal)
+uniform vec3 lightAtten[MAX_LIGHTS];
+uniform vec2 lightCone[MAX_LIGHTS];
+uniform vec3 viewPos;
+
+// Material del objeto (ver material.rs)
+uniform vec3 objectColor;      // Albedo lineal
+uniform float ambientStrength;
+uniform float metallic;
+uniform float roughness;
+uniform vec3 emissiveColor;
+
+// Mapas de normales y de altura (relieve.rs hace lo mismo en el trazador)
+uniform sampler2D normalMap;   // Espacio tangente, verde hacia arriba en la imagen (OpenGL)
+uniform sampler2D heightMap;   // Blanco = alto
+uniform int useNormalMap;
+uniform int useHeightMap;
+uniform float normalStrength;
+uniform float parallaxScale;   // Profundidad en unidades de UV
+
+// Entorno (ver entorno.rs): mapa equirectangular lineal con mipmaps e irradiancia en armónicos esféricos
+uniform int useEnvironment;        // 1 = iluminación basada en imagen
+uniform sampler2D environmentMap;
+uniform vec3 environmentSH[9];     // Irradiancia ya convolucionada con el coseno
+uniform float environmentIntensity;
+uniform float environmentRotation; // Radianes alrededor de Y
+uniform float environmentMaxLod;   // Mip que corresponde a roughness 1
+
+uniform mat4 lightVP[MAX_LIGHTS];
+uniform sampler2D shadowMap0;
+uniform sampler2D shadowMap1;
+uniform sampler2D shadowMap2;
+uniform sampler2D shadowMap3;
+uniform int shadowCount;
+uniform int shadowMapResolution;
+uniform int pcfRadius;
+
+out vec4 finalColor;
+
+vec3 rotateEnvironment(vec3 d) {
+    float s = sin(-environmentRotation);
+    float c = cos(-environmentRotation);
+    return vec3(d.x * c + d.z * s, d.y, -d.x * s + d.z * c);
+}
+
+vec2 equirectUV(vec3 d) {
+    return vec2(0.5 + atan(d.z, d.x) / (2.0 * PI), acos(clamp(d.y, -1.0, 1.0)) / PI);
+}
+
+// Irradiancia difusa (mismo cálculo que Entorno::irradiance)
+vec3 irradianceSH(vec3 n) {
+    vec3 d = rotateEnvironment(n);
+    vec3 e = environmentSH[0] * 0.282095
+        + environmentSH[1] * 0.488603 * d.y
+        + environmentSH[2] * 0.488603 * d.z
+        + environmentSH[3] * 0.488603 * d.x
+        + environmentSH[4] * 1.092548 * d.x * d.y
+        + environmentSH[5] * 1.092548 * d.y * d.z
+        + environmentSH[6] * 0.315392 * (3.0 * d.z * d.z - 1.0)
+        + environmentSH[7] * 1.092548 * d.x * d.z
+        + environmentSH[8] * 0.546274 * (d.x * d.x - d.y * d.y);
+    return max(e, vec3(0.0)) * environmentIntensity;
+}
+
+// Reflejo prefiltrado: más rugoso, mip más borroso (mismo criterio que Entorno::sample_rough)
+vec3 environmentSpecular(vec3 R, float r) {
+    return textureLod(environmentMap, equirectUV(rotateEnvironment(R)), r * environmentMaxLod).rgb * environmentIntensity;
+}
+
+// Aproximación analítica de la integral de la BRDF para el entorno (Karis); la misma que pbr::env_brdf
+vec3 envBRDF(vec3 F0, float r, float NdotV) {
+    const vec4 c0 = vec4(-1.0, -0.0275, -0.572, 0.022);
+    const vec4 c1 = vec4(1.0, 0.0425, 1.04, -0.04);
+    vec4 k = r * c0 + c1;
+    float a004 = min(k.x * k.x, exp2(-9.28 * NdotV)) * k.x + k.y;
+    vec2 AB = vec2(-1.04, 1.04) * a004 + k.zw;
+    return F0 * AB.x + AB.y;
+}
+
+// Base tangente (dP/du, dP/dv, N) a partir de las derivadas de la posición y la UV:
+// no hace falta enviar tangentes por vértice
+mat3 cotangentFrame(vec3 N, vec3 p, vec2 uv) {
+    vec3 dp1 = dFdx(p);
+    vec3 dp2 = dFdy(p);
+    vec2 duv1 = dFdx(uv);
+    vec2 duv2 = dFdy(uv);
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+    return mat3(T * inversesqrt(max(dot(T, T), 1e-20)), B * inversesqrt(max(dot(B, B), 1e-20)), N);
+}
+
+// Parallax occlusion mapping con la vista en espacio tangente (mismo algoritmo que relieve::parallax_uv)
+vec2 parallaxUV(vec2 uv, vec3 viewTS) {
+    if (viewTS.z <= 1e-4) return uv;
+    float layers = mix(32.0, 8.0, min(viewTS.z, 1.0));
+    float layerDepth = 1.0 / layers;
+    vec2 delta = viewTS.xy / viewTS.z * parallaxScale / layers;
+
+    vec2 currentUV = uv;
+    float currentDepth = 0.0;
+    float mapDepth = 1.0 - textureLod(heightMap, currentUV, 0.0).r;
+    for (int i = 0; i < 32 && currentDepth < mapDepth; i++) {
+        currentUV -= delta;
+        mapDepth = 1.0 - textureLod(heightMap, currentUV, 0.0).r;
+        currentDepth += layerDepth;
+    }
+
+    // Interpolar entre la última capa sobre la superficie y la primera por debajo
+    vec2 previousUV = currentUV + delta;
+    float after = mapDepth - currentDepth;
+    float before = (1.0 - textureLod(heightMap, previousUV, 0.0).r) - (currentDepth - layerDepth);
+    float weight = abs(after - before) > 1e-6 ? after / (after - before) : 0.0;
+    return mix(currentUV, previousUV, weight);
+}
+
+float sampleShadowMap(int i, vec2 uv) {
+    if (i == 0) return texture(shadowMap0, uv).r;
+    if (i == 1) return texture(shadowMap1, uv).r;
+    if (i == 2) return texture(shadowMap2, uv).r;
+    return texture(shadowMap3, uv).r;
+}
+
+float shadowFactor(int i, vec3 N, vec3 L) {
+    if (i >= shadowCount) return 1.0;
+
+    vec4 lightSpace = lightVP[i] * vec4(fragPos, 1.0);
+    vec3 proj = lightSpace.xyz / lightSpace.w * 0.5 + 0.5;
+    if (proj.z > 1.0 || proj.x < 0.0 || proj.x > 1.0 || proj.y < 0.0 || proj.y > 1.0) return 1.0;
+
+    float bias = max(0.0005 * (1.0 - dot(N, L)), 0.00005);
+    float texel = 1.0 / float(shadowMapResolution);
+
+    float lit = 0.0;
+    int samples = 0;
+    for (int x = -pcfRadius; x <= pcfRadius; x++) {
+        for (int y = -pcfRadius; y <= pcfRadius; y++) {
+            float depth = sampleShadowMap(i, proj.xy + vec2(x, y) * texel);
+            lit += (proj.z - bias > depth) ? 0.0 : 1.0;
+            samples++;
+        }
+    }
+    return lit / float(samples);
+}
+
+float distributionGGX(float NdotH, float r) {
+    float a = r * r;
+    float a2 = a * a;
+    float denom = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denom * denom);
+}
+
+float geometrySchlickGGX(float NdotX, float r) {
+    float k = (r + 1.0) * (r + 1.0) / 8.0;
+    return NdotX / (NdotX * (1.0 - k) + k);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 F0) {
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main() {
+    vec3 N = normalize(normal);
+    vec3 V = normalize(viewPos - fragPos);
+    // Las caras se ven por ambos lados, igual que en el trazador
+    if (dot(N, V) < 0.0) N = -N;
+
+    // Relieve: la UV se desplaza con el mapa de alturas y la normal sale del mapa de normales
+    vec2 uv = fragTexCoord;
+    if (useNormalMap == 1 || useHeightMap == 1) {
+        mat3 TBN = cotangentFrame(N, fragPos, fragTexCoord);
+        if (useHeightMap == 1) uv = parallaxUV(uv, transpose(TBN) * V);
+        if (useNormalMap == 1) {
+            vec3 n = texture(normalMap, uv).rgb * 2.0 - 1.0;
+            N = normalize(TBN[0] * (n.x * normalStrength) - TBN[1] * (n.y * normalStrength) + N * n.z);
+        }
+    }
+
+    vec4 texel = texture(texture0, uv);
+    vec3 albedo = objectColor * srgbToLinear(texel.rgb) * srgbToLinear(fragColor.rgb);
+
+    float r = clamp(roughness, 0.04, 1.0);
+    float m = clamp(metallic, 0.0, 1.0);
+    vec3 F0 = mix(vec3(0.04), albedo, m);
+    float NdotV = max(dot(N, V), 1e-4);
+
+    vec3 Lo = vec3(0.0);
+    for (int i = 0; i < lightCount; i++) {
+        vec3 L;
+        float attenuation = 1.0;
+        if (lightType[i] == LIGHT_DIRECTIONAL) {
+            L = normalize(-lightDir[i]);
+        } else {
+            vec3 toLight = lightPos[i] - fragPos;
+            float d = length(toLight);
+            L = toLight / d;
+            attenuation = 1.0 / (lightAtten[i].x + lightAtten[i].y * d + lightAtten[i].z * d * d);
+            if (lightType[i] == LIGHT_SPOT) {
+                float cosTheta = dot(-L, normalize(lightDir[i]));
+                attenuation *= smoothstep(lightCone[i].y, lightCone[i].x, cosTheta);
+            }
+        }
+
+        float NdotL = max(dot(N, L), 0.0);
+        if (NdotL <= 0.0) continue;
+
+        vec3 H = normalize(V + L);
+        float D = distributionGGX(max(dot(N, H), 0.0), r);
+        float G = geometrySchlickGGX(NdotV, r) * geometrySchlickGGX(NdotL, r);
+        vec3 F = fresnelSchlick(max(dot(V, H), 0.0), F0);
+
+        vec3 specular = D * G * F / (4.0 * NdotV * NdotL);
+        // Lo que no se refleja se difunde; los metales no tienen difuso
+        vec3 kD = (1.0 - F) * (1.0 - m);
+        vec3 diffuse = kD * albedo / PI;
+
+        // El color de la luz es la irradiancia en incidencia normal, de ahí el factor PI
+        vec3 radiance = lightColor[i] * attenuation * PI;
+        Lo += (diffuse + specular) * radiance * NdotL * shadowFactor(i, N, L);
+    }
+
+    // Ambiente con el color de la primera luz (sólo la parte difusa)
+    vec3 ambientLight = lightCount > 0 ? lightColor[0] : vec3(1.0);
+    vec3 ambient = ambientStrength * ambientLight * albedo * (1.0 - m);
+    if (useEnvironment == 1) {
+        // Iluminación basada en imagen: difuso de la irradiancia y especular del mip según la rugosidad
+        vec3 F = envBRDF(F0, r, NdotV);
+        ambient = (1.0 - F) * (1.0 - m) * albedo * irradianceSH(N) / PI
+            + F * environmentSpecular(reflect(-V, N), r);
+    }
+
+    vec3 color = ambient + Lo + emissiveColor;
+    // Lineal y sin recortar: el framebuffer es HDR y la pasada de tonemap.fs lo lleva a sRGB
+    finalColor = vec4(color, texel.a * fragColor.a);
+}
@@ -0,0 +1,55 @@
+#version 330 core
+
+// Profundidad de campo: cada píxel se desenfoca con un disco cuyo radio crece al alejarse
+// de la distancia de foco (círculo de confusión), leyendo la profundidad del framebuffer
+
+#define SAMPLES 32
+#define GOLDEN_ANGLE 2.39996323
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;      // Imagen HDR
+uniform sampler2D depthTexture;  // Profundidad del framebuffer (0..1)
+uniform vec2 resolution;
+uniform float cameraNear;
+uniform float cameraFar;
+uniform float focusDistance;
+uniform float focusRange;
+uniform float maxBlur;           // Píxeles
+
+out vec4 finalColor;
+
+float linearDepth(vec2 uv) {
+    float z = texture(depthTexture, uv).r * 2.0 - 1.0;
+    return 2.0 * cameraNear * cameraFar / (cameraFar + cameraNear - z * (cameraFar - cameraNear));
+}
+
+// Radio del desenfoque en píxeles
+float circleOfConfusion(vec2 uv) {
+    float distanceToFocus = abs(linearDepth(uv) - focusDistance) - focusRange * 0.5;
+    return clamp(distanceToFocus / max(focusRange, 1e-3), 0.0, 1.0) * maxBlur;
+}
+
+void main() {
+    float coc = circleOfConfusion(fragTexCoord);
+    vec3 color = texture(texture0, fragTexCoord).rgb;
+    if (coc < 0.5) {
+        finalColor = vec4(color, 1.0);
+        return;
+    }
+
+    // Espiral de ángulo dorado; las muestras nítidas que quedan fuera de su propio círculo
+    // pesan menos para que lo enfocado no se derrame sobre el fondo
+    vec3 sum = color;
+    float total = 1.0;
+    for (int i = 1; i < SAMPLES; i++) {
+        float r = sqrt(float(i) / float(SAMPLES)) * coc;
+        float theta = float(i) * GOLDEN_ANGLE;
+        vec2 uv = fragTexCoord + vec2(cos(theta), sin(theta)) * r / resolution;
+        float weight = smoothstep(r - 1.0, r + 1.0, circleOfConfusion(uv));
+        sum += texture(texture0, uv).rgb * weight;
+        total += weight;
+    }
+    finalColor = vec4(sum / total, 1.0);
+}
@@ -0,0 +1,35 @@
+#version 330 core
+
+// Fondo de la escena: el mapa de entorno equirectangular visto desde la cámara (ver entorno.rs).
+// Usa lighting.vs; el cubo del skybox se envía en espacio mundo alrededor de la cámara
+
+#define PI 3.14159265
+
+in vec3 fragPos;
+in vec3 normal;
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;    // Mapa equirectangular en lineal
+uniform vec3 viewPos;
+uniform float environmentIntensity;
+uniform float environmentRotation; // Radianes alrededor de Y
+
+out vec4 finalColor;
+
+vec3 rotateEnvironment(vec3 d) {
+    float s = sin(-environmentRotation);
+    float c = cos(-environmentRotation);
+    return vec3(d.x * c + d.z * s, d.y, -d.x * s + d.z * c);
+}
+
+vec2 equirectUV(vec3 d) {
+    return vec2(0.5 + atan(d.z, d.x) / (2.0 * PI), acos(clamp(d.y, -1.0, 1.0)) / PI);
+}
+
+void main() {
+    vec3 dir = normalize(fragPos - viewPos);
+    // Nivel 0 fijo: con mipmaps automáticos la costura de atan2 se vería como una línea
+    vec3 color = textureLod(texture0, equirectUV(rotateEnvironment(dir)), 0.0).rgb * environmentIntensity;
+    finalColor = vec4(color, 1.0);
+}
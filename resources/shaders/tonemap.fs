@@ -0,0 +1,43 @@
+#version 330 core
+
+// Pasada final de framebuffer::blit_to_screen: exposición, operador de tono y salida en sRGB.
+// Mismas curvas que src/tonos.rs; usa el vertex shader por defecto de raylib
+
+// Mismos valores que Tonemapper::shader_id
+#define TONEMAP_LINEAR 0
+#define TONEMAP_REINHARD 1
+#define TONEMAP_ACES 2
+#define TONEMAP_FILMIC 3
+
+#define FILMIC_WHITE 11.2
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;   // Framebuffer HDR en lineal
+uniform float exposure;       // Multiplicador, 2^EV
+uniform int tonemapper;
+
+out vec4 finalColor;
+
+vec3 hable(vec3 x) {
+    const float A = 0.15, B = 0.50, C = 0.10, D = 0.20, E = 0.02, F = 0.30;
+    return (x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F) - E / F;
+}
+
+vec3 tonemap(vec3 c) {
+    c = max(c, vec3(0.0));
+    if (tonemapper == TONEMAP_REINHARD) return c / (1.0 + c);
+    if (tonemapper == TONEMAP_ACES) return (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14);
+    if (tonemapper == TONEMAP_FILMIC) return hable(2.0 * c) / hable(vec3(FILMIC_WHITE));
+    return c;
+}
+
+vec3 linearToSrgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+void main() {
+    vec3 hdr = texture(texture0, fragTexCoord).rgb * exposure;
+    finalColor = vec4(linearToSrgb(clamp(tonemap(hdr), 0.0, 1.0)), 1.0);
+}
@@ -0,0 +1,23 @@
+#version 330 core
+
+// Modos por CPU (ver shaders::VertexColorShader): la luz llega en el color de vértice comprimida
+// con c / (1 + c) (tonos::encode_vertex_color) y se expande aquí para el framebuffer HDR.
+// Usa el vertex shader por defecto de raylib
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main() {
+    vec4 texel = texture(texture0, fragTexCoord);
+    vec3 light = fragColor.rgb / max(1.0 - fragColor.rgb, vec3(1.0 / 255.0));
+    finalColor = vec4(srgbToLinear(texel.rgb) * light * colDiffuse.rgb, texel.a * fragColor.a * colDiffuse.a);
+}
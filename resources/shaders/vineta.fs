@@ -0,0 +1,20 @@
+#version 330 core
+
+// Viñeta: oscurece hacia las esquinas a partir de `radius` (distancia al centro, 1 = esquina)
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform float intensity;
+uniform float radius;
+uniform float softness;
+
+out vec4 finalColor;
+
+void main() {
+    vec3 color = texture(texture0, fragTexCoord).rgb;
+    float d = distance(fragTexCoord, vec2(0.5)) * 1.41421356;
+    float vignette = smoothstep(radius, radius - softness, d);
+    finalColor = vec4(color * mix(1.0, vignette, intensity), 1.0);
+}
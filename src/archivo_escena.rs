@@ -0,0 +1,830 @@
+use raylib::prelude::*;
+use serde::Deserialize;
+use crate::{archivo_malla::{self, PiezaMalla}, camera, controles::LightControls, material::Material, primitivas};
+use crate::cubo::{self, CaraTextura, Cubo};
+use crate::entorno::{self, Entorno};
+use crate::{objeto::{Forma, Objeto}, piso::{Cuadricula, Piso}, transform::Transform};
+use crate::light::{Light, LightKind, MAX_LIGHTS};
+use crate::tonos::{Tonemap, Tonemapper};
+use crate::posproceso::{self, Efecto, PostSettings};
+use crate::sombras::{self, ShadowSettings};
+
+// Descripción de una escena en TOML (ver escenas/default.toml).
+// Todos los campos son opcionales; lo que falte toma los valores por defecto.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SceneFile {
+    pub background: [u8; 3],
+    pub objects: Vec<ObjectDesc>,
+    pub floor: FloorDesc,
+    pub lights: Vec<LightDesc>,
+    pub camera: CameraDesc,
+    pub environment: Option<EnvironmentDesc>, // Sin entorno el fondo es `background`
+    pub tonemap: TonemapDesc,
+    pub post: PostDesc,
+    pub shadows: ShadowsDesc,
+    #[serde(skip)]
+    pub entorno: Option<Entorno>, // Entorno ya cargado (SceneFile::load)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectKind {
+    #[default]
+    Cube,
+    Mesh, // Archivo OBJ o glTF indicado en `file`
+    // Primitivas procedurales (ver primitivas.rs); caben en [-size, size] como el cubo
+    Sphere,
+    Icosphere,
+    Cylinder,
+    Cone,
+    Torus,
+    Plane,
+    #[serde(rename = "subdivided_cube")]
+    SubdividedCube,
+}
+
+// Un objeto de la lista [[objects]]
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObjectDesc {
+    #[serde(rename = "type")]
+    pub kind: ObjectKind,
+    pub size: f32, // Cubo: media arista (mide 2 * size); malla: escala uniforme
+    pub position: Option<[f32; 3]>, // Centro del cubo u origen de la malla; sin posición se apoya sobre el piso en el origen
+    pub rotation: [f32; 3], // Ángulos de Euler en grados (X, Y, Z)
+    pub scale: [f32; 3],
+    pub segments: u32, // Divisiones alrededor de esferas, cilindros, conos y toros
+    pub subdivisions: u32, // Icosfera, plano y cubo subdividido
+    pub file: Option<String>, // Malla .obj, .gltf o .glb
+    pub texture: Option<String>, // En mallas reemplaza las texturas de sus materiales
+    pub layout: CubeLayout, // Cubo: cómo se reparte `texture` entre las caras
+    pub cubemap: Vec<String>, // Cubo: seis imágenes, en el orden +X, -X, +Y, -Y, +Z, -Z
+    pub faces: FacesDesc, // Cubo: textura o región propia de algunas caras
+    pub material: MaterialDesc, // En mallas modifica los materiales del archivo
+    #[serde(skip)]
+    pub piezas: Vec<PiezaMalla>, // Malla ya cargada (SceneFile::load)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CubeLayout {
+    #[default]
+    Single, // La imagen entera en cada cara
+    Cross, // Cruz horizontal de 4x3 (ver cubo::cross_layout)
+}
+
+// Tabla [objects.faces]; cada cara es opcional
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FacesDesc {
+    pub front: Option<FaceDesc>, // z+
+    pub back: Option<FaceDesc>, // z-
+    pub right: Option<FaceDesc>, // x+
+    pub left: Option<FaceDesc>, // x-
+    pub top: Option<FaceDesc>, // y+
+    pub bottom: Option<FaceDesc>, // y-
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FaceDesc {
+    pub texture: Option<String>, // Sin textura usa la del objeto
+    pub region: Option<[f32; 4]>, // x, y, ancho, alto dentro de la imagen (0..1, origen arriba a la izquierda)
+}
+
+// Tabla [objects.material] / [floor.material]; lo que falte sale del material por defecto
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaterialDesc {
+    pub color: Option<[f32; 3]>, // Albedo (0..1); con textura, por defecto blanco
+    pub ambient: Option<f32>,
+    pub diffuse: Option<f32>,
+    pub specular: Option<f32>,
+    pub shininess: Option<f32>,
+    pub emissive: Option<[f32; 3]>,
+    pub metallic: Option<f32>,
+    pub roughness: Option<f32>,
+    pub normal_map: Option<String>,
+    pub height_map: Option<String>, // Parallax; blanco = alto
+    pub normal_strength: Option<f32>,
+    pub parallax_scale: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FloorDesc {
+    pub y: f32,
+    pub size: f32,
+    pub texture: Option<String>,
+    pub tiling: f32, // Veces que se repite la textura por lado
+    pub grid: bool, // Cuadrícula superpuesta
+    pub grid_cells: u32,
+    pub grid_fade: f32, // Distancia a la cámara a la que la cuadrícula desaparece
+    pub material: MaterialDesc,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LightType {
+    #[default]
+    Point,
+    Directional,
+    Spot,
+}
+
+// Una luz de la lista [[lights]] (como máximo MAX_LIGHTS)
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LightDesc {
+    #[serde(rename = "type")]
+    pub kind: LightType,
+    pub position: [f32; 3], // Define radio, altura y ángulo de la órbita
+    pub direction: [f32; 3], // Direccional y foco
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub attenuation: [f32; 3], // Constante, lineal, cuadrática (puntual y foco)
+    pub inner_angle: f32, // Semiángulos del cono en grados (foco)
+    pub outer_angle: f32,
+    pub auto_rotate: bool,
+}
+
+// Tabla [environment]: una imagen equirectangular o seis caras de cubemap
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvironmentDesc {
+    pub equirect: Option<String>, // .hdr/.exr en lineal; otras imágenes se toman como sRGB
+    pub cubemap: Vec<String>, // Seis imágenes, en el orden +X, -X, +Y, -Y, +Z, -Z
+    pub intensity: f32,
+    pub rotation: f32, // Grados alrededor del eje Y
+    pub ibl: bool, // Además de verse de fondo, ilumina (ambiente y reflejos)
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TonemapType {
+    Linear,
+    Reinhard,
+    #[default]
+    Aces,
+    Filmic,
+}
+
+// Tabla [tonemap]: cómo se lleva la imagen HDR a la pantalla (y al PNG del trazador)
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TonemapDesc {
+    pub operator: TonemapType,
+    pub exposure: f32, // En pasos (EV); se cambia en la ventana con , y .
+}
+
+// Tabla [shadows]: shadow maps de los modos con shader; en la ventana se cambian con [ / ] (y Shift)
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShadowsDesc {
+    pub resolution: i32, // Ancho y alto de cada mapa de profundidad en píxeles
+    pub pcf_radius: i32, // Radio del filtro PCF en texels (0 = sombras duras)
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectType {
+    Bloom,
+    DepthOfField,
+    Fxaa,
+    ColorGrading,
+    ChromaticAberration,
+    Vignette,
+}
+
+// Tabla [post]: cada subtabla activa su efecto (salvo `enabled = false`); con F1..F6 se alternan en la ventana
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostDesc {
+    pub order: Vec<EffectType>, // Los que falten van después, en el orden por defecto
+    pub bloom: Option<BloomDesc>,
+    pub depth_of_field: Option<DepthOfFieldDesc>,
+    pub fxaa: Option<FxaaDesc>,
+    pub color_grading: Option<ColorGradingDesc>,
+    pub chromatic_aberration: Option<ChromaticAberrationDesc>,
+    pub vignette: Option<VignetteDesc>,
+}
+
+// Parámetros de cada efecto; lo que falte toma el valor por defecto (ver src/posproceso.rs)
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BloomDesc {
+    pub enabled: Option<bool>,
+    pub threshold: Option<f32>,
+    pub intensity: Option<f32>,
+    pub radius: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepthOfFieldDesc {
+    pub enabled: Option<bool>,
+    pub focus_distance: Option<f32>,
+    pub focus_range: Option<f32>,
+    pub max_blur: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FxaaDesc {
+    pub enabled: Option<bool>,
+    pub span_max: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorGradingDesc {
+    pub enabled: Option<bool>,
+    pub lut: Option<String>,
+    pub strength: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChromaticAberrationDesc {
+    pub enabled: Option<bool>,
+    pub strength: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct VignetteDesc {
+    pub enabled: Option<bool>,
+    pub intensity: Option<f32>,
+    pub radius: Option<f32>,
+    pub softness: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraDesc {
+    pub position: [f32; 3],
+    pub target: Option<[f32; 3]>, // Sin target se mira al primer objeto
+    pub fovy: f32,
+}
+
+impl Default for SceneFile {
+    fn default() -> Self {
+        Self {
+            background: [20, 25, 35],
+            objects: vec![ObjectDesc::default()],
+            floor: FloorDesc::default(),
+            lights: vec![LightDesc::default()],
+            camera: CameraDesc::default(),
+            environment: None,
+            tonemap: TonemapDesc::default(),
+            post: PostDesc::default(),
+            shadows: ShadowsDesc::default(),
+            entorno: None,
+        }
+    }
+}
+
+impl Default for ShadowsDesc {
+    fn default() -> Self {
+        let settings = ShadowSettings::default();
+        Self { resolution: settings.resolution, pcf_radius: settings.pcf_radius }
+    }
+}
+
+impl Default for EnvironmentDesc {
+    fn default() -> Self {
+        Self { equirect: None, cubemap: Vec::new(), intensity: 1.0, rotation: 0.0, ibl: true }
+    }
+}
+
+impl Default for ObjectDesc {
+    fn default() -> Self {
+        Self {
+            kind: ObjectKind::Cube,
+            size: 1.0,
+            position: None,
+            rotation: [0.0, 0.0, 0.0],
+            scale: [1.0, 1.0, 1.0],
+            segments: 32,
+            subdivisions: 3,
+            file: None,
+            layout: CubeLayout::Single,
+            cubemap: Vec::new(),
+            faces: FacesDesc::default(),
+            texture: Some("assets/texture1.png".to_string()),
+            material: MaterialDesc::default(),
+            piezas: Vec::new(),
+        }
+    }
+}
+
+impl Default for FloorDesc {
+    fn default() -> Self {
+        Self {
+            y: -1.5,
+            size: 8.0,
+            texture: None,
+            tiling: 1.0,
+            grid: true,
+            grid_cells: 10,
+            grid_fade: 15.0,
+            material: MaterialDesc::default(),
+        }
+    }
+}
+
+impl Default for LightDesc {
+    fn default() -> Self {
+        Self {
+            kind: LightType::Point,
+            position: [5.0, 4.0, 0.0],
+            direction: [-1.0, -1.0, 0.0],
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            attenuation: [1.0, 0.05, 0.0],
+            inner_angle: 20.0,
+            outer_angle: 30.0,
+            auto_rotate: true,
+        }
+    }
+}
+
+impl Default for CameraDesc {
+    fn default() -> Self {
+        Self {
+            position: [5.0, 2.0, 5.0],
+            target: None,
+            fovy: 45.0,
+        }
+    }
+}
+
+impl SceneFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer la escena {}: {}", path, e))?;
+        let mut scene: Self = toml::from_str(&text).map_err(|e| format!("Escena inválida {}: {}", path, e))?;
+        scene.load_meshes()?;
+        scene.check(path)?;
+        scene.entorno = scene.environment.as_ref().map(EnvironmentDesc::load).transpose()?;
+        Ok(scene)
+    }
+
+    // Valores que darían NaN, infinitos o una vista rota: se rechazan al cargar con un mensaje
+    fn check(&self, path: &str) -> Result<(), String> {
+        self.check_cubemaps()?;
+        for (i, object) in self.objects.iter().enumerate() {
+            object.check().map_err(|e| format!("Objeto {} de {}: {}", i + 1, path, e))?;
+        }
+        for (i, light) in self.lights.iter().enumerate() {
+            light.check().map_err(|e| format!("Luz {} de {}: {}", i + 1, path, e))?;
+        }
+        self.shadows.check()?;
+        self.camera.check().map_err(|e| format!("Cámara de {}: {}", path, e))
+    }
+
+    fn check_cubemaps(&self) -> Result<(), String> {
+        match self.objects.iter().find(|o| !o.cubemap.is_empty() && o.cubemap.len() != 6) {
+            Some(object) => Err(format!("`cubemap` necesita 6 imágenes (+X, -X, +Y, -Y, +Z, -Z), tiene {}", object.cubemap.len())),
+            None => Ok(()),
+        }
+    }
+
+    // Carga una vez los archivos de las mallas; sus texturas entran en texture_paths
+    fn load_meshes(&mut self) -> Result<(), String> {
+        for object in self.objects.iter_mut().filter(|o| matches!(o.kind, ObjectKind::Mesh)) {
+            let file = object.file.as_ref().ok_or("Un objeto de tipo \"mesh\" necesita `file`")?;
+            object.piezas = archivo_malla::load(file)?;
+        }
+        Ok(())
+    }
+
+    pub fn background_color(&self) -> Color {
+        Color::new(self.background[0], self.background[1], self.background[2], 255)
+    }
+
+    // Rutas de textura sin repetir; el índice de cada una es el que usa Material::texture
+    pub fn texture_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        let floor = [&self.floor.texture, &self.floor.material.normal_map, &self.floor.material.height_map];
+        for path in self.objects.iter().flat_map(ObjectDesc::texture_paths).chain(floor.into_iter().flatten()) {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        paths
+    }
+
+    pub fn make_camera(&self, focus: Vector3) -> Camera3D {
+        let target = self.camera.target.map_or(focus, vec3);
+        camera::make_camera(vec3(self.camera.position), target, self.camera.fovy)
+    }
+
+    // Las luces que sobran por encima de MAX_LIGHTS se ignoran
+    pub fn make_lights(&self) -> Vec<Light> {
+        self.lights.iter().take(MAX_LIGHTS).map(LightDesc::build).collect()
+    }
+
+    // Cada luz orbita alrededor del eje Y partiendo de su posición inicial
+    pub fn make_light_controls(&self, lights: &[Light]) -> LightControls {
+        let mut controls = LightControls::new(lights);
+        for (orbit, desc) in controls.orbits.iter_mut().zip(&self.lights) {
+            orbit.color = desc.color;
+            orbit.intensity = desc.intensity;
+            orbit.auto_rotate = desc.auto_rotate;
+        }
+        controls
+    }
+}
+
+impl ObjectDesc {
+    // Las normales se transforman dividiendo por la escala (Transform::transform_normal): ningún eje puede ser 0
+    fn check(&self) -> Result<(), String> {
+        if self.scale.iter().any(|s| *s == 0.0 || !s.is_finite()) {
+            return Err(format!("`scale` no puede tener componentes nulos, es {:?}", self.scale));
+        }
+        if self.size == 0.0 || !self.size.is_finite() {
+            return Err(format!("`size` no puede ser 0, es {}", self.size));
+        }
+        Ok(())
+    }
+
+    // Un cubo o una primitiva dan un objeto; una malla, uno por cada material del archivo
+    pub fn build(&self, floor_y: f32, texture_paths: &[String]) -> Vec<Objeto> {
+        let texture_index = |path: Option<&String>| path.and_then(|t| texture_paths.iter().position(|p| p == t));
+        // Con textura es la textura la que aporta el color, salvo que se indique uno
+        let base = if self.texture_paths().is_empty() { Material::default() } else { Material::new(Vector3::one()) };
+        match self.kind {
+            ObjectKind::Cube => {
+                // Apoyado sobre el piso según su altura escalada (sin tener en cuenta la rotación)
+                let center = self.position.map_or(Vector3::new(0.0, floor_y + self.size * self.scale[1], 0.0), vec3);
+                let transform = Transform::from_euler_degrees(center, vec3(self.rotation), vec3(self.scale));
+                let texture = texture_index(self.texture.as_ref());
+                let mut cubo = Cubo::new(self.size, transform);
+                cubo.caras = self.build_caras(&texture_index);
+                vec![Objeto::new(Forma::Cubo(Box::new(cubo)), self.build_material(base, texture, &texture_index))]
+            }
+            ObjectKind::Mesh => {
+                self.build_piezas(&self.piezas, vec3(self.scale) * self.size, floor_y, texture_index)
+            }
+            _ => {
+                // La primitiva ya tiene el tamaño; `size` no se vuelve a aplicar como escala
+                let pieza = PiezaMalla { malla: self.primitiva().into_malla(), material: base, texture: None };
+                self.build_piezas(std::slice::from_ref(&pieza), vec3(self.scale), floor_y, texture_index)
+            }
+        }
+    }
+
+    // Primero el reparto de `layout`, luego las imágenes de `cubemap` y por último [objects.faces]
+    fn build_caras(&self, texture_index: &impl Fn(Option<&String>) -> Option<usize>) -> [CaraTextura; 6] {
+        let mut caras = match self.layout {
+            CubeLayout::Single => [CaraTextura::default(); 6],
+            CubeLayout::Cross => cubo::cross_layout(),
+        };
+        let cubemap_order = [cubo::RIGHT, cubo::LEFT, cubo::TOP, cubo::BOTTOM, cubo::FRONT, cubo::BACK];
+        for (path, &i) in self.cubemap.iter().zip(&cubemap_order) {
+            caras[i] = CaraTextura { texture: texture_index(Some(path)), ..CaraTextura::default() };
+        }
+        for (i, face) in self.faces.by_index() {
+            if let Some(path) = &face.texture {
+                caras[i] = CaraTextura { texture: texture_index(Some(path)), ..CaraTextura::default() };
+            }
+            if let Some([x, y, width, height]) = face.region {
+                caras[i].region = Rectangle::new(x, y, width, height);
+            }
+        }
+        caras
+    }
+
+    fn primitiva(&self) -> primitivas::Primitiva {
+        let s = self.size;
+        match self.kind {
+            ObjectKind::Sphere => primitivas::uv_sphere(s, self.segments, self.segments / 2),
+            ObjectKind::Icosphere => primitivas::icosphere(s, self.subdivisions),
+            ObjectKind::Cylinder => primitivas::cylinder(s, s, self.segments),
+            ObjectKind::Cone => primitivas::cone(s, s, self.segments),
+            // El tubo ocupa el 30% exterior del radio
+            ObjectKind::Torus => primitivas::torus(s * 0.7, s * 0.3, self.segments, self.segments / 2),
+            ObjectKind::Plane => primitivas::plane(s, self.subdivisions),
+            ObjectKind::SubdividedCube => primitivas::subdivided_cube(s, self.subdivisions),
+            ObjectKind::Cube | ObjectKind::Mesh => unreachable!("no es una primitiva"),
+        }
+    }
+
+    fn build_piezas(&self, piezas: &[PiezaMalla], scale: Vector3, floor_y: f32,
+                    texture_index: impl Fn(Option<&String>) -> Option<usize>) -> Vec<Objeto> {
+        // Apoyada sobre el piso: la base de su caja envolvente queda en floor_y
+        let center = self.position.map_or_else(|| {
+            let min_y = piezas.iter().map(|p| p.malla.bounds().0.y).fold(f32::INFINITY, f32::min);
+            Vector3::new(0.0, floor_y - min_y * scale.y, 0.0)
+        }, vec3);
+        let transform = Transform::from_euler_degrees(center, vec3(self.rotation), scale);
+        piezas.iter()
+            .map(|pieza| {
+                let texture = texture_index(self.texture.as_ref().or(pieza.texture.as_ref()));
+                let forma = Forma::Malla(pieza.malla.transformed(&transform));
+                Objeto::new(forma, self.build_material(pieza.material, texture, &texture_index))
+            })
+            .collect()
+    }
+
+    fn build_material(&self, base: Material, texture: Option<usize>,
+                      texture_index: &impl Fn(Option<&String>) -> Option<usize>) -> Material {
+        let mut material = self.material.build(base);
+        material.texture = texture;
+        material.normal_map = texture_index(self.material.normal_map.as_ref()).or(material.normal_map);
+        material.height_map = texture_index(self.material.height_map.as_ref()).or(material.height_map);
+        material
+    }
+
+    fn texture_paths(&self) -> Vec<&String> {
+        let mut paths = match &self.texture {
+            Some(path) => vec![path],
+            None => self.piezas.iter().filter_map(|p| p.texture.as_ref()).collect(),
+        };
+        paths.extend(&self.cubemap);
+        paths.extend(self.material.normal_map.iter().chain(&self.material.height_map));
+        paths.extend(self.faces.by_index().filter_map(|(_, face)| face.texture.as_ref()));
+        paths
+    }
+}
+
+impl FacesDesc {
+    // Caras indicadas junto a su índice en Cubo::caras
+    fn by_index(&self) -> impl Iterator<Item = (usize, &FaceDesc)> {
+        [
+            (cubo::FRONT, &self.front),
+            (cubo::BACK, &self.back),
+            (cubo::RIGHT, &self.right),
+            (cubo::LEFT, &self.left),
+            (cubo::TOP, &self.top),
+            (cubo::BOTTOM, &self.bottom),
+        ].into_iter().filter_map(|(i, face)| face.as_ref().map(|f| (i, f)))
+    }
+}
+
+impl FloorDesc {
+    pub fn build(&self, texture_paths: &[String]) -> Piso {
+        let texture_index = |path: &Option<String>| path.as_ref().and_then(|t| texture_paths.iter().position(|p| p == t));
+        // Con textura, como en los objetos, el color base es blanco salvo que se indique uno
+        let base = match self.texture {
+            Some(_) => Material { albedo: Vector3::one(), ..Piso::default_material() },
+            None => Piso::default_material(),
+        };
+        let mut material = self.material.build(base);
+        material.texture = texture_index(&self.texture);
+        material.normal_map = texture_index(&self.material.normal_map);
+        material.height_map = texture_index(&self.material.height_map);
+
+        let mut piso = Piso::new(self.y, self.size, material);
+        piso.tiling = self.tiling;
+        piso.grid = self.grid.then(|| Cuadricula { cells: self.grid_cells, fade_distance: self.grid_fade, ..Cuadricula::default() });
+        piso
+    }
+}
+
+impl MaterialDesc {
+    pub fn build(&self, base: Material) -> Material {
+        Material {
+            albedo: self.color.map_or(base.albedo, vec3),
+            ambient: self.ambient.unwrap_or(base.ambient),
+            diffuse: self.diffuse.unwrap_or(base.diffuse),
+            specular: self.specular.unwrap_or(base.specular),
+            shininess: self.shininess.unwrap_or(base.shininess),
+            emissive: self.emissive.map_or(base.emissive, vec3),
+            metallic: self.metallic.unwrap_or(base.metallic),
+            roughness: self.roughness.unwrap_or(base.roughness),
+            normal_strength: self.normal_strength.unwrap_or(base.normal_strength),
+            parallax_scale: self.parallax_scale.unwrap_or(base.parallax_scale),
+            ..base
+        }
+    }
+}
+
+impl EnvironmentDesc {
+    pub fn load(&self) -> Result<Entorno, String> {
+        let image = match (&self.equirect, self.cubemap.len()) {
+            (Some(path), _) => entorno::load_equirect(path)?,
+            (None, 6) => entorno::load_cubemap(&self.cubemap)?,
+            _ => return Err("[environment] necesita `equirect` o `cubemap` con 6 imágenes (+X, -X, +Y, -Y, +Z, -Z)".to_string()),
+        };
+        Ok(Entorno::new(image, self.intensity, self.rotation, self.ibl))
+    }
+}
+
+impl TonemapDesc {
+    pub fn build(&self) -> Tonemap {
+        let tonemapper = match self.operator {
+            TonemapType::Linear => Tonemapper::Linear,
+            TonemapType::Reinhard => Tonemapper::Reinhard,
+            TonemapType::Aces => Tonemapper::Aces,
+            TonemapType::Filmic => Tonemapper::Filmic,
+        };
+        Tonemap { tonemapper, exposure: self.exposure }
+    }
+}
+
+impl CameraDesc {
+    // Con la cámara sobre su objetivo no hay dirección de la mirada (CameraControls daría yaw y pitch NaN)
+    fn check(&self) -> Result<(), String> {
+        match self.target {
+            Some(target) if target == self.position => {
+                Err(format!("`target` no puede coincidir con `position` ({:?})", self.position))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ShadowsDesc {
+    fn check(&self) -> Result<(), String> {
+        if !(sombras::MIN_RESOLUTION..=sombras::MAX_RESOLUTION).contains(&self.resolution) {
+            return Err(format!("`shadows.resolution` debe estar entre {} y {}, es {}",
+                               sombras::MIN_RESOLUTION, sombras::MAX_RESOLUTION, self.resolution));
+        }
+        if !(0..=sombras::MAX_PCF_RADIUS).contains(&self.pcf_radius) {
+            return Err(format!("`shadows.pcf_radius` debe estar entre 0 y {}, es {}", sombras::MAX_PCF_RADIUS, self.pcf_radius));
+        }
+        Ok(())
+    }
+
+    pub fn build(&self) -> ShadowSettings {
+        ShadowSettings { resolution: self.resolution, pcf_radius: self.pcf_radius }
+    }
+}
+
+impl PostDesc {
+    pub fn build(&self) -> PostSettings {
+        let mut order: Vec<Efecto> = Vec::new();
+        for efecto in self.order.iter().map(|e| e.build()).chain(Efecto::ALL) {
+            if !order.contains(&efecto) {
+                order.push(efecto);
+            }
+        }
+
+        let d = PostSettings::default();
+        PostSettings {
+            order,
+            bloom: self.bloom.as_ref().map_or(d.bloom.clone(), |b| posproceso::Bloom {
+                enabled: b.enabled.unwrap_or(true),
+                threshold: b.threshold.unwrap_or(d.bloom.threshold),
+                intensity: b.intensity.unwrap_or(d.bloom.intensity),
+                radius: b.radius.unwrap_or(d.bloom.radius),
+            }),
+            depth_of_field: self.depth_of_field.as_ref().map_or(d.depth_of_field.clone(), |f| posproceso::DepthOfField {
+                enabled: f.enabled.unwrap_or(true),
+                focus_distance: f.focus_distance.unwrap_or(d.depth_of_field.focus_distance),
+                focus_range: f.focus_range.unwrap_or(d.depth_of_field.focus_range),
+                max_blur: f.max_blur.unwrap_or(d.depth_of_field.max_blur),
+            }),
+            fxaa: self.fxaa.as_ref().map_or(d.fxaa.clone(), |f| posproceso::Fxaa {
+                enabled: f.enabled.unwrap_or(true),
+                span_max: f.span_max.unwrap_or(d.fxaa.span_max),
+            }),
+            color_grading: self.color_grading.as_ref().map_or(d.color_grading.clone(), |g| posproceso::ColorGrading {
+                enabled: g.enabled.unwrap_or(true),
+                lut: g.lut.clone(),
+                strength: g.strength.unwrap_or(d.color_grading.strength),
+            }),
+            chromatic_aberration: self.chromatic_aberration.as_ref().map_or(d.chromatic_aberration.clone(), |c| {
+                posproceso::ChromaticAberration {
+                    enabled: c.enabled.unwrap_or(true),
+                    strength: c.strength.unwrap_or(d.chromatic_aberration.strength),
+                }
+            }),
+            vignette: self.vignette.as_ref().map_or(d.vignette.clone(), |v| posproceso::Vignette {
+                enabled: v.enabled.unwrap_or(true),
+                intensity: v.intensity.unwrap_or(d.vignette.intensity),
+                radius: v.radius.unwrap_or(d.vignette.radius),
+                softness: v.softness.unwrap_or(d.vignette.softness),
+            }),
+        }
+    }
+}
+
+impl EffectType {
+    fn build(self) -> Efecto {
+        match self {
+            EffectType::Bloom => Efecto::Bloom,
+            EffectType::DepthOfField => Efecto::DepthOfField,
+            EffectType::Fxaa => Efecto::Fxaa,
+            EffectType::ColorGrading => Efecto::ColorGrading,
+            EffectType::ChromaticAberration => Efecto::ChromaticAberration,
+            EffectType::Vignette => Efecto::Vignette,
+        }
+    }
+}
+
+impl LightDesc {
+    // Valores con los que la atenuación o el cono del foco darían infinitos o NaN
+    fn check(&self) -> Result<(), String> {
+        let uses_distance = matches!(self.kind, LightType::Point | LightType::Spot);
+        let [c, l, q] = self.attenuation;
+        if uses_distance && (c <= 0.0 || l < 0.0 || q < 0.0) {
+            return Err(format!("`attenuation` necesita constante > 0 y lineal y cuadrática >= 0, es {:?}", self.attenuation));
+        }
+        if !matches!(self.kind, LightType::Point) && vec3(self.direction).length() == 0.0 {
+            return Err("`direction` no puede ser [0, 0, 0]".to_string());
+        }
+        let cone = (0.0..self.outer_angle).contains(&self.inner_angle) && self.outer_angle <= 180.0;
+        if matches!(self.kind, LightType::Spot) && !cone {
+            return Err(format!("el foco necesita 0 <= `inner_angle` < `outer_angle` <= 180, son {} y {}",
+                               self.inner_angle, self.outer_angle));
+        }
+        Ok(())
+    }
+
+    pub fn build(&self) -> Light {
+        let mut light = Light::new(self.position, self.color.map(|c| c * self.intensity));
+        light.kind = match self.kind {
+            LightType::Point => LightKind::Point,
+            LightType::Directional => LightKind::Directional,
+            LightType::Spot => LightKind::Spot,
+        };
+        light.direction = self.direction;
+        light.attenuation = self.attenuation;
+        light.inner_angle = self.inner_angle;
+        light.outer_angle = self.outer_angle;
+        light
+    }
+}
+
+fn vec3(v: [f32; 3]) -> Vector3 {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str) -> Result<(), String> {
+        let scene: SceneFile = toml::from_str(text).expect("TOML de prueba inválido");
+        scene.check("prueba.toml")
+    }
+
+    // Rechazada y con el campo culpable en el mensaje
+    fn assert_rejected(text: &str, field: &str) {
+        match check(text) {
+            Ok(()) => panic!("Se aceptó una escena con `{}` inválido:\n{}", field, text),
+            Err(e) => assert!(e.contains(field), "El mensaje no nombra `{}`: {}", field, e),
+        }
+    }
+
+    #[test]
+    fn default_and_example_values_pass() {
+        assert!(check("").is_ok());
+        assert!(check("[[lights]]\ntype = \"spot\"\ninner_angle = 10.0\nouter_angle = 90.0").is_ok());
+        // La atenuación no se usa en las direccionales
+        assert!(check("[[lights]]\ntype = \"directional\"\nattenuation = [0.0, 0.0, 0.0]").is_ok());
+        assert!(check("[camera]\nposition = [0.0, 2.0, 5.0]\ntarget = [0.0, 0.0, 0.0]").is_ok());
+    }
+
+    #[test]
+    fn rejects_attenuation_that_divides_by_zero_or_grows_with_distance() {
+        assert_rejected("[[lights]]\nattenuation = [0.0, 0.0, 0.0]", "attenuation");
+        assert_rejected("[[lights]]\ntype = \"spot\"\nattenuation = [0.0, 0.1, 0.0]", "attenuation");
+        assert_rejected("[[lights]]\nattenuation = [1.0, -0.1, 0.0]", "attenuation");
+        assert_rejected("[[lights]]\nattenuation = [1.0, 0.0, -0.1]", "attenuation");
+    }
+
+    #[test]
+    fn rejects_spot_cones_without_a_falloff() {
+        assert_rejected("[[lights]]\ntype = \"spot\"\ninner_angle = 30.0\nouter_angle = 30.0", "inner_angle");
+        assert_rejected("[[lights]]\ntype = \"spot\"\ninner_angle = 40.0\nouter_angle = 30.0", "inner_angle");
+        assert_rejected("[[lights]]\ntype = \"spot\"\ninner_angle = -5.0", "inner_angle");
+        assert_rejected("[[lights]]\ntype = \"spot\"\nouter_angle = 190.0", "outer_angle");
+    }
+
+    #[test]
+    fn rejects_lights_without_direction() {
+        assert_rejected("[[lights]]\ntype = \"directional\"\ndirection = [0.0, 0.0, 0.0]", "direction");
+        assert_rejected("[[lights]]\ntype = \"spot\"\ndirection = [0.0, 0.0, 0.0]", "direction");
+    }
+
+    #[test]
+    fn rejects_zero_scale_and_size() {
+        assert_rejected("[[objects]]\nscale = [1.0, 0.0, 1.0]", "scale");
+        assert_rejected("[[objects]]\nsize = 0.0", "size");
+    }
+
+    #[test]
+    fn rejects_shadow_settings_out_of_range() {
+        assert_rejected("[shadows]\nresolution = 0", "resolution");
+        assert_rejected("[shadows]\nresolution = 100000", "resolution");
+        assert_rejected("[shadows]\npcf_radius = -1", "pcf_radius");
+        assert_rejected("[shadows]\npcf_radius = 5", "pcf_radius");
+    }
+
+    #[test]
+    fn rejects_camera_on_its_target() {
+        assert_rejected("[camera]\nposition = [1.0, 2.0, 3.0]\ntarget = [1.0, 2.0, 3.0]", "target");
+    }
+
+    #[test]
+    fn rejects_incomplete_cubemaps() {
+        assert_rejected("[[objects]]\ncubemap = [\"a.png\", \"b.png\"]", "cubemap");
+    }
+}
+
+// Módulo para cargar escenas desde disco
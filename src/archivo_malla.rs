@@ -0,0 +1,464 @@
+use image::RgbaImage;
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::malla::{Malla, Vertice};
+use crate::material::Material;
+
+// Parte de un archivo de malla con un único material
+pub struct PiezaMalla {
+    pub malla: Malla,                // En el espacio del archivo
+    pub material: Material,          // Sin textura asignada (ver `texture`)
+    pub texture: Option<String>,     // Textura de albedo, relativa al directorio de trabajo (ver `load_texture`)
+}
+
+// Carga una malla según la extensión: Wavefront OBJ (con su MTL) o glTF 2.0 (.gltf / .glb)
+pub fn load(path: &str) -> Result<Vec<PiezaMalla>, String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let piezas = match extension.as_str() {
+        "obj" => load_obj(path)?,
+        "gltf" | "glb" => load_gltf(path)?,
+        _ => return Err(format!("Formato de malla no soportado: {}", path)),
+    };
+    if piezas.is_empty() {
+        return Err(format!("La malla {} no tiene triángulos", path));
+    }
+    Ok(piezas)
+}
+
+// Imagen embebida en un glTF (en un buffer view del .glb o en un URI "data:"): "modelo.glb#<índice de la imagen>"
+fn embedded_path(file: &str, image: usize) -> String {
+    format!("{}#{}", file, image)
+}
+
+fn split_embedded(path: &str) -> Option<(&str, usize)> {
+    let (file, index) = path.rsplit_once('#')?;
+    let extension = Path::new(file).extension()?.to_str()?.to_lowercase();
+    if !matches!(extension.as_str(), "gltf" | "glb") {
+        return None;
+    }
+    Some((file, index.parse().ok()?))
+}
+
+// Textura como imagen RGBA (trazador por CPU): un archivo o una imagen embebida en un glTF.
+// Las imágenes vacías se rechazan: el trazador muestrea sin comprobar el tamaño
+pub fn load_texture_image(path: &str) -> Result<RgbaImage, String> {
+    let image = decode_texture_image(path)?;
+    if image.width() == 0 || image.height() == 0 {
+        return Err(format!("La textura {} está vacía ({}x{})", path, image.width(), image.height()));
+    }
+    Ok(image)
+}
+
+fn decode_texture_image(path: &str) -> Result<RgbaImage, String> {
+    let Some((file, index)) = split_embedded(path) else {
+        return image::open(path).map(|img| img.to_rgba8())
+            .map_err(|e| format!("No se pudo cargar la textura {}: {}", path, e));
+    };
+    let (_, _, images) = gltf::import(file)
+        .map_err(|e| format!("No se pudo leer la malla {}: {}", file, e))?;
+    let data = images.into_iter().nth(index)
+        .ok_or_else(|| format!("La malla {} no tiene la imagen {}", file, index))?;
+
+    use gltf::image::Format;
+    let rgba = match data.format {
+        Format::R8G8B8A8 => data.pixels,
+        Format::R8G8B8 => data.pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        Format::R8G8 => data.pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        Format::R8 => data.pixels.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        _ => return Err(format!("Formato de la imagen {} no soportado (sólo 8 bits por canal)", path)),
+    };
+    RgbaImage::from_raw(data.width, data.height, rgba)
+        .ok_or_else(|| format!("Tamaño de imagen inconsistente en {}", path))
+}
+
+// Textura en la GPU: los archivos los carga raylib; las imágenes embebidas se decodifican con gltf y se suben
+pub fn load_texture(rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Result<Texture2D, String> {
+    if split_embedded(path).is_none() {
+        return rl.load_texture(thread, path).map_err(|e| format!("No se pudo cargar la textura {}: {}", path, e));
+    }
+    let image = load_texture_image(path)?;
+    unsafe {
+        // LoadTextureFromImage copia los píxeles: la imagen puede liberarse después
+        let raw = raylib::ffi::Image {
+            data: image.as_ptr() as *mut std::ffi::c_void,
+            width: image.width() as i32,
+            height: image.height() as i32,
+            mipmaps: 1,
+            format: raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+        };
+        Ok(Texture2D::from_raw(raylib::ffi::LoadTextureFromImage(raw)))
+    }
+}
+
+// Rutas dentro de un archivo (mtllib, map_Kd, uri) son relativas al propio archivo
+fn relative_to(file: &str, path: &str) -> String {
+    Path::new(file).parent().unwrap_or(Path::new("")).join(path).to_string_lossy().into_owned()
+}
+
+// Material de un MTL antes de convertirlo
+struct MaterialObj {
+    material: Material,
+    texture: Option<String>,
+}
+
+fn load_obj(path: &str) -> Result<Vec<PiezaMalla>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("No se pudo leer la malla {}: {}", path, e))?;
+    parse_obj(&text, path)
+}
+
+// `path` sólo se usa en los mensajes de error y para resolver mtllib (el único acceso a disco)
+fn parse_obj(text: &str, path: &str) -> Result<Vec<PiezaMalla>, String> {
+    let mut positions: Vec<Vector3> = Vec::new();
+    let mut uvs: Vec<Vector2> = Vec::new();
+    let mut normals: Vec<Vector3> = Vec::new();
+    let mut materials: HashMap<String, MaterialObj> = HashMap::new();
+    // Triángulos agrupados por material, en el orden en que aparecen
+    let mut groups: Vec<(String, Vec<[Vertice; 3]>)> = vec![(String::new(), Vec::new())];
+
+    for (line_number, line) in text.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        let Some(keyword) = parts.next() else { continue };
+        let args: Vec<&str> = parts.collect();
+        let error = |what: &str| format!("{}:{}: {}", path, line_number + 1, what);
+
+        match keyword {
+            "v" => positions.push(parse_vec3(&args).ok_or_else(|| error("vértice inválido"))?),
+            // OBJ tiene el origen de las UV abajo; las texturas de raylib, arriba
+            "vt" => {
+                let u = args.first().and_then(|v| v.parse().ok()).ok_or_else(|| error("UV inválida"))?;
+                let v: f32 = args.get(1).and_then(|v| v.parse().ok()).unwrap_or(0.0);
+                uvs.push(Vector2::new(u, 1.0 - v));
+            }
+            "vn" => normals.push(parse_vec3(&args).ok_or_else(|| error("normal inválida"))?.normalized()),
+            "mtllib" => {
+                for file in &args {
+                    materials.extend(load_mtl(&relative_to(path, file))?);
+                }
+            }
+            "usemtl" => groups.push((args.join(" "), Vec::new())),
+            "f" => {
+                let corners = args.iter()
+                    .map(|c| parse_corner(c, &positions, &uvs, &normals))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| error("cara inválida"))?;
+                // Polígonos en abanico desde el primer vértice
+                let triangles = &mut groups.last_mut().expect("siempre hay un grupo").1;
+                for i in 1..corners.len().saturating_sub(1) {
+                    triangles.push(with_face_normal([corners[0], corners[i], corners[i + 1]]));
+                }
+            }
+            _ => {} // o, g, s, l... no afectan a la geometría que dibujamos
+        }
+    }
+
+    Ok(groups.into_iter()
+        .filter(|(_, triangles)| !triangles.is_empty())
+        .map(|(name, triangles)| {
+            let (material, texture) = match materials.get(&name) {
+                Some(m) => (m.material, m.texture.clone()),
+                None => (Material::default(), None),
+            };
+            PiezaMalla { malla: Malla::new(triangles), material, texture }
+        })
+        .collect())
+}
+
+fn parse_vec3(args: &[&str]) -> Option<Vector3> {
+    let mut it = args.iter().map(|a| a.parse::<f32>());
+    Some(Vector3::new(it.next()?.ok()?, it.next()?.ok()?, it.next()?.ok()?))
+}
+
+// Índices de OBJ: empiezan en 1; los negativos cuentan desde el final
+fn resolve_index(index: &str, len: usize) -> Option<usize> {
+    let i: i64 = index.parse().ok()?;
+    let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+    (resolved >= 0 && (resolved as usize) < len).then_some(resolved as usize)
+}
+
+// Esquina "v", "v/vt", "v//vn" o "v/vt/vn"; sin normal queda en cero y se calcula luego
+fn parse_corner(corner: &str, positions: &[Vector3], uvs: &[Vector2], normals: &[Vector3]) -> Option<Vertice> {
+    let mut parts = corner.split('/');
+    let pos = positions[resolve_index(parts.next()?, positions.len())?];
+    let uv = match parts.next() {
+        Some(t) if !t.is_empty() => uvs[resolve_index(t, uvs.len())?],
+        _ => Vector2::zero(),
+    };
+    let normal = match parts.next() {
+        Some(n) if !n.is_empty() => normals[resolve_index(n, normals.len())?],
+        _ => Vector3::zero(),
+    };
+    Some(Vertice { pos, normal, uv })
+}
+
+// Los vértices sin normal usan la normal de la cara
+fn with_face_normal(mut tri: [Vertice; 3]) -> [Vertice; 3] {
+    let face = (tri[1].pos - tri[0].pos).cross(tri[2].pos - tri[0].pos).normalized();
+    for v in tri.iter_mut().filter(|v| v.normal.length() == 0.0) {
+        v.normal = face;
+    }
+    tri
+}
+
+// MTL: Kd, Ks, Ns, Ke, map_Kd y las extensiones PBR Pm / Pr
+fn load_mtl(path: &str) -> Result<HashMap<String, MaterialObj>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("No se pudo leer el material {}: {}", path, e))?;
+    Ok(parse_mtl(&text, path))
+}
+
+// Las rutas de map_Kd son relativas a `path`
+fn parse_mtl(text: &str, path: &str) -> HashMap<String, MaterialObj> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MaterialObj)> = None;
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(keyword) = parts.next() else { continue };
+        let args: Vec<&str> = parts.collect();
+
+        if keyword == "newmtl" {
+            materials.extend(current.take());
+            let obj = MaterialObj { material: Material::new(Vector3::one()), texture: None };
+            current = Some((args.join(" "), obj));
+            continue;
+        }
+        let Some((_, obj)) = current.as_mut() else { continue };
+        let value = args.first().and_then(|v| v.parse::<f32>().ok());
+        match keyword {
+            "Kd" => if let Some(c) = parse_vec3(&args) { obj.material.albedo = c },
+            "Ks" => if let Some(c) = parse_vec3(&args) { obj.material.specular = (c.x + c.y + c.z) / 3.0 },
+            "Ke" => if let Some(c) = parse_vec3(&args) { obj.material.emissive = c },
+            "Ns" => if let Some(v) = value { obj.material.shininess = v.max(1.0) },
+            "Pm" => if let Some(v) = value { obj.material.metallic = v },
+            "Pr" => if let Some(v) = value { obj.material.roughness = v },
+            // Las opciones (-s, -o, ...) van antes del nombre del archivo
+            "map_Kd" => obj.texture = args.last().map(|file| relative_to(path, file)),
+            _ => {}
+        }
+    }
+    materials.extend(current);
+    materials
+}
+
+fn load_gltf(path: &str) -> Result<Vec<PiezaMalla>, String> {
+    let (document, buffers, _) = gltf::import(path)
+        .map_err(|e| format!("No se pudo leer la malla {}: {}", path, e))?;
+
+    let mut piezas = Vec::new();
+    let scene = document.default_scene().or_else(|| document.scenes().next())
+        .ok_or_else(|| format!("La malla {} no tiene escenas", path))?;
+    for node in scene.nodes() {
+        collect_gltf_node(path, &node, IDENTITY, &buffers, &mut piezas);
+    }
+    Ok(piezas)
+}
+
+type Mat4 = [[f32; 4]; 4]; // Por columnas, como en glTF
+
+const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn mat_mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut r = [[0.0; 4]; 4];
+    for (c, col) in r.iter_mut().enumerate() {
+        for (row, value) in col.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b[c][k]).sum();
+        }
+    }
+    r
+}
+
+fn mat_point(m: &Mat4, p: Vector3) -> Vector3 {
+    Vector3::new(
+        m[0][0] * p.x + m[1][0] * p.y + m[2][0] * p.z + m[3][0],
+        m[0][1] * p.x + m[1][1] * p.y + m[2][1] * p.z + m[3][1],
+        m[0][2] * p.x + m[1][2] * p.y + m[2][2] * p.z + m[3][2],
+    )
+}
+
+// Inversa transpuesta de la parte 3x3: las normales siguen perpendiculares con escala no uniforme.
+// Sus columnas son los productos cruz de las columnas de la matriz divididos por el determinante;
+// como se normaliza, basta con su signo (una escala negativa da vuelta las normales)
+fn mat_normal(m: &Mat4, n: Vector3) -> Vector3 {
+    let [c0, c1, c2] = [0, 1, 2].map(|c| Vector3::new(m[c][0], m[c][1], m[c][2]));
+    let det = c0.dot(c1.cross(c2));
+    let n = c1.cross(c2) * n.x + c2.cross(c0) * n.y + c0.cross(c1) * n.z;
+    (n * det.signum()).normalized()
+}
+
+// Recorre la jerarquía acumulando las transformaciones de los nodos
+fn collect_gltf_node(path: &str, node: &gltf::Node, parent: Mat4, buffers: &[gltf::buffer::Data], piezas: &mut Vec<PiezaMalla>) {
+    let world = mat_mul(&parent, &node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(|b| buffers.get(b.index()).map(|d| &d.0[..]));
+            let Some(positions) = reader.read_positions() else { continue };
+            let positions: Vec<Vector3> = positions.map(|p| mat_point(&world, Vector3::new(p[0], p[1], p[2]))).collect();
+            let normals: Vec<Vector3> = reader.read_normals()
+                .map(|it| it.map(|n| mat_normal(&world, Vector3::new(n[0], n[1], n[2]))).collect())
+                .unwrap_or_default();
+            let uvs: Vec<Vector2> = reader.read_tex_coords(0)
+                .map(|it| it.into_f32().map(|t| Vector2::new(t[0], t[1])).collect())
+                .unwrap_or_default();
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(it) => it.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+
+            let vertex = |i: u32| {
+                let i = i as usize;
+                Vertice {
+                    pos: positions[i],
+                    normal: normals.get(i).copied().unwrap_or(Vector3::zero()),
+                    uv: uvs.get(i).copied().unwrap_or(Vector2::zero()),
+                }
+            };
+            let triangles = indices.chunks_exact(3)
+                .filter(|t| t.iter().all(|&i| (i as usize) < positions.len()))
+                .map(|t| with_face_normal([vertex(t[0]), vertex(t[1]), vertex(t[2])]))
+                .collect();
+
+            let (material, texture) = gltf_material(path, &primitive.material());
+            piezas.push(PiezaMalla { malla: Malla::new(triangles), material, texture });
+        }
+    }
+
+    for child in node.children() {
+        collect_gltf_node(path, &child, world, buffers, piezas);
+    }
+}
+
+// Material metallic/roughness de glTF; la textura de albedo puede ser externa o estar embebida
+fn gltf_material(path: &str, material: &gltf::Material) -> (Material, Option<String>) {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, _] = pbr.base_color_factor();
+    let [er, eg, eb] = material.emissive_factor();
+    let result = Material {
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        emissive: Vector3::new(er, eg, eb),
+        ..Material::new(Vector3::new(r, g, b))
+    };
+    let texture = pbr.base_color_texture().map(|info| {
+        let image = info.texture().source();
+        match image.source() {
+            gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => relative_to(path, uri),
+            _ => embedded_path(path, image.index()),
+        }
+    });
+    (result, texture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_index_counts_from_one_and_negatives_from_the_end() {
+        assert_eq!(resolve_index("1", 3), Some(0));
+        assert_eq!(resolve_index("3", 3), Some(2));
+        assert_eq!(resolve_index("-1", 3), Some(2));
+        assert_eq!(resolve_index("-3", 3), Some(0));
+    }
+
+    #[test]
+    fn resolve_index_rejects_out_of_range_and_garbage() {
+        assert_eq!(resolve_index("0", 3), None);
+        assert_eq!(resolve_index("4", 3), None);
+        assert_eq!(resolve_index("-4", 3), None);
+        assert_eq!(resolve_index("x", 3), None);
+        assert_eq!(resolve_index("1", 0), None);
+    }
+
+    #[test]
+    fn parse_corner_reads_every_layout() {
+        let positions = [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 2.0, 0.0)];
+        let uvs = [Vector2::new(0.25, 0.5)];
+        let normals = [Vector3::new(0.0, 0.0, 1.0)];
+
+        let v = parse_corner("2", &positions, &uvs, &normals).unwrap();
+        assert_eq!((v.pos, v.uv, v.normal), (positions[1], Vector2::zero(), Vector3::zero()));
+
+        let v = parse_corner("1/1", &positions, &uvs, &normals).unwrap();
+        assert_eq!((v.pos, v.uv, v.normal), (positions[0], uvs[0], Vector3::zero()));
+
+        let v = parse_corner("2//1", &positions, &uvs, &normals).unwrap();
+        assert_eq!((v.pos, v.uv, v.normal), (positions[1], Vector2::zero(), normals[0]));
+
+        let v = parse_corner("-1/-1/-1", &positions, &uvs, &normals).unwrap();
+        assert_eq!((v.pos, v.uv, v.normal), (positions[1], uvs[0], normals[0]));
+    }
+
+    #[test]
+    fn parse_corner_rejects_missing_indices() {
+        let positions = [Vector3::zero()];
+        assert!(parse_corner("2", &positions, &[], &[]).is_none());
+        assert!(parse_corner("1/1", &positions, &[], &[]).is_none());
+        assert!(parse_corner("1//1", &positions, &[], &[]).is_none());
+        assert!(parse_corner("", &positions, &[], &[]).is_none());
+    }
+
+    #[test]
+    fn obj_polygons_are_triangulated_as_a_fan() {
+        let text = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0.5 2 0\nv 0 1 0\nf 1 2 3 4 5\n";
+        let piezas = parse_obj(text, "modelos/abanico.obj").unwrap();
+        assert_eq!(piezas.len(), 1);
+
+        let triangles = piezas[0].malla.triangles();
+        assert_eq!(triangles.len(), 3);
+        // Todos comparten el primer vértice y siguen el orden del polígono
+        let v = [Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 0.0),
+                 Vector3::new(0.5, 2.0, 0.0), Vector3::new(0.0, 1.0, 0.0)];
+        assert_eq!(triangles, vec![(v[0], v[1], v[2]), (v[0], v[2], v[3]), (v[0], v[3], v[4])]);
+    }
+
+    #[test]
+    fn obj_corners_without_normal_use_the_face_normal() {
+        let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 1 0 0\nf 1//1 2 3\n";
+        let piezas = parse_obj(text, "modelos/normales.obj").unwrap();
+        let normals: Vec<Vector3> = piezas[0].malla.textured_triangles()[0].verts.iter().map(|v| v.normal).collect();
+        assert_eq!(normals, vec![Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, 1.0)]);
+    }
+
+    #[test]
+    fn parse_mtl_reads_colors_pbr_values_and_texture() {
+        let text = "\
+# Comentario
+newmtl rojo
+Kd 0.8 0.1 0.1
+Ks 0.3 0.6 0.9
+Ns 0.5
+Pm 1.0
+Pr 0.25
+map_Kd -s 2 2 1 ladrillo.png
+
+newmtl con espacios
+Ke 1 2 3
+";
+        let materials = parse_mtl(text, "modelos/materiales.mtl");
+        assert_eq!(materials.len(), 2);
+
+        let rojo = &materials["rojo"];
+        assert_eq!(rojo.material.albedo, Vector3::new(0.8, 0.1, 0.1));
+        assert!((rojo.material.specular - 0.6).abs() < 1e-6);
+        assert_eq!(rojo.material.shininess, 1.0); // Ns por debajo de 1 se recorta
+        assert_eq!((rojo.material.metallic, rojo.material.roughness), (1.0, 0.25));
+        assert_eq!(rojo.texture, Some(relative_to("modelos/materiales.mtl", "ladrillo.png")));
+
+        let otro = &materials["con espacios"];
+        assert_eq!(otro.material.albedo, Vector3::one());
+        assert_eq!(otro.material.emissive, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(otro.texture, None);
+    }
+}
+
+// Módulo para cargar mallas desde disco
@@ -0,0 +1,109 @@
+use raylib::prelude::*;
+use image::{imageops, Rgb32FImage, RgbImage};
+use std::path::{Path, PathBuf};
+use crate::{framebuffer::Framebuffer, posproceso::PostProceso};
+
+// Lee la luz lineal del framebuffer HDR (sin tonemap ni post-proceso), para EXR
+pub fn read_hdr(fb: &Framebuffer) -> Rgb32FImage {
+    let texture = *fb.texture().as_ref();
+    let (width, height) = (texture.width as u32, texture.height as u32);
+    let pixels = unsafe {
+        let mut image = raylib::ffi::LoadImageFromTexture(texture);
+        // raylib convierte los half float (o los 8 bits del framebuffer de respaldo) a f32
+        raylib::ffi::ImageFormat(&mut image, raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R32G32B32 as i32);
+        let data = std::slice::from_raw_parts(image.data as *const f32, (width * height * 3) as usize).to_vec();
+        raylib::ffi::UnloadImage(image);
+        data
+    };
+    let mut img = Rgb32FImage::from_raw(width, height, pixels).expect("Tamaño de imagen inconsistente");
+    // Mismo flip vertical que corrige blit_to_screen
+    imageops::flip_vertical_in_place(&mut img);
+    img
+}
+
+// Lee un render texture de 8 bits (la imagen final, ya en sRGB), para PNG y JPEG
+pub fn read_ldr(target: &RenderTexture2D) -> RgbImage {
+    let texture = *target.texture().as_ref();
+    let (width, height) = (texture.width as u32, texture.height as u32);
+    let pixels = unsafe {
+        let mut image = raylib::ffi::LoadImageFromTexture(texture);
+        raylib::ffi::ImageFormat(&mut image, raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8 as i32);
+        let data = std::slice::from_raw_parts(image.data as *const u8, (width * height * 3) as usize).to_vec();
+        raylib::ffi::UnloadImage(image);
+        data
+    };
+    let mut img = RgbImage::from_raw(width, height, pixels).expect("Tamaño de imagen inconsistente");
+    imageops::flip_vertical_in_place(&mut img);
+    img
+}
+
+// Guarda el último fotograma; el formato sale de la extensión. PNG y JPEG llevan la imagen tal
+// como se ve (tonemap y post-proceso), EXR la luz lineal del framebuffer
+pub fn save(path: &Path, fb: &Framebuffer, post: &PostProceso) -> Result<(), String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let result = match extension.as_str() {
+        "png" | "jpg" | "jpeg" => read_ldr(post.output()).save(path),
+        "exr" => read_hdr(fb).save(path),
+        _ => return Err(format!("Formato de captura no soportado: {} (usa .png, .jpg o .exr)", path.display())),
+    };
+    result.map_err(|e| format!("No se pudo guardar {}: {}", path.display(), e))
+}
+
+// Primer `captura_NNN.<extension>` que no existe en el directorio actual
+pub fn next_screenshot_path(extension: &str) -> PathBuf {
+    (0..)
+        .map(|i| PathBuf::from(format!("captura_{:03}.{}", i, extension)))
+        .find(|path| !path.exists())
+        .expect("Sin nombres de captura libres")
+}
+
+// Guarda cada fotograma numerado a partir de un patrón: "capturas/orbita.png" escribe
+// capturas/orbita_00000.png, capturas/orbita_00001.png, ... (para armar un video de la órbita de la luz)
+pub struct Secuencia {
+    pattern: PathBuf,
+    start: u32,             // Número del primer fotograma en el nombre de archivo
+    pub frame: u32,         // Fotogramas guardados
+    pub limit: Option<u32>, // Se detiene sola después de tantos fotogramas
+}
+
+impl Secuencia {
+    pub fn new(pattern: &str, limit: Option<u32>) -> Self {
+        Self { pattern: PathBuf::from(pattern), start: 0, frame: 0, limit }
+    }
+
+    // Toma nueva que sigue la numeración en el primer número libre, como next_screenshot_path:
+    // grabar otra vez con el mismo patrón no pisa la toma anterior
+    pub fn next_free(pattern: &str) -> Self {
+        let mut seq = Self::new(pattern, None);
+        seq.start = (0..).find(|&i| !seq.path_for(i).exists()).expect("Sin nombres de fotograma libres");
+        seq
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.limit.is_some_and(|limit| self.frame >= limit)
+    }
+
+    pub fn frame_path(&self) -> PathBuf {
+        self.path_for(self.start + self.frame)
+    }
+
+    fn path_for(&self, number: u32) -> PathBuf {
+        let stem = self.pattern.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+        let extension = self.pattern.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        self.pattern.with_file_name(format!("{}_{:05}.{}", stem, number, extension))
+    }
+
+    // Guarda el fotograma actual y avanza el contador; crea la carpeta la primera vez
+    pub fn capture(&mut self, fb: &Framebuffer, post: &PostProceso) -> Result<(), String> {
+        if self.frame == 0 {
+            if let Some(dir) = self.pattern.parent().filter(|d| !d.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir).map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+            }
+        }
+        save(&self.frame_path(), fb, post)?;
+        self.frame += 1;
+        Ok(())
+    }
+}
+
+// Módulo para capturas de pantalla y secuencias de imágenes
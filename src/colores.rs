@@ -1,10 +1,42 @@
-// Color base del objeto - más claro para mejor visibilidad de la iluminación
-pub const OBJECT_COLOR: [f32; 3] = [0.9, 0.4, 0.3]; // Rojo más intenso
-
-// Configuración de iluminación mejorada para contraste extremo
-pub const AMBIENT_LIGHT: f32 = 0.001; // Luz ambiente casi nula para contraste extremo
-pub const DIFFUSE_STRENGTH: f32 = 2.0; // Intensidad difusa muy alta
-pub const SPECULAR_STRENGTH: f32 = 0.6; // Intensidad especular alta
+// Luz que queda en las zonas en sombra (sombras trazadas); las propiedades de superficie están en material.rs
 pub const SHADOW_INTENSITY: f32 = 0.01; // Sombras muy pronunciadas
 
+// RGB (0..1) -> HSV con el tono en grados
+pub fn rgb_to_hsv(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation, max]
+}
+
+// HSV (tono en grados) -> RGB (0..1)
+pub fn hsv_to_rgb(hsv: [f32; 3]) -> [f32; 3] {
+    let [h, s, v] = hsv;
+    let c = v * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    [r + m, g + m, b + m]
+}
+
 // Módulo para colores
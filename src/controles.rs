@@ -1,9 +1,85 @@
 use raylib::prelude::*;
+use crate::{colores, entrada::{Action, InputMap, Stick, Trigger}, light::{Light, LightKind}};
+
+use std::f32::consts::PI;
+
+// Todas las velocidades son por segundo y se multiplican por el tiempo del fotograma (dt),
+// así los controles se sienten igual a 30, 60 o 144 Hz
+
+// Límite de inclinación de la cámara (órbita y mirada) para no pasar por los polos
+const MAX_PITCH: f32 = 1.4;
+// Órbita con teclado (radianes/s) y sensibilidad del mouse (radianes por píxel)
+const ORBIT_SPEED: f32 = 1.2;
+const MOUSE_SENSITIVITY: f32 = 0.005;
+// Zoom con teclado (unidades/s) y distancia que recorre cada paso de la rueda
+const ZOOM_SPEED: f32 = 4.8;
+const WHEEL_ZOOM: f32 = 0.5;
+const MIN_RADIUS: f32 = 2.0;
+const MAX_RADIUS: f32 = 30.0;
+// Inercia: qué tan rápido (1/s) la velocidad de giro y de zoom alcanza la pedida por las
+// teclas; al soltarlas decae con la misma tasa y la cámara se detiene suavemente
+const ORBIT_DAMPING: f32 = 8.0;
+const ZOOM_DAMPING: f32 = 8.0;
+// Vuelo libre y primera persona: unidades/s; Shift multiplica la velocidad
+const FLY_SPEED: f32 = 4.8;
+const WALK_SPEED: f32 = 3.0;
+const RUN_MULTIPLIER: f32 = 3.0;
+// Primera persona: altura de los ojos sobre el piso y distancia mínima al borde
+const EYE_HEIGHT: f32 = 1.6;
+const WALK_MARGIN: f32 = 0.2;
+// Tasa (1/s) con la que los ojos se acercan a su altura al pasar a caminar
+const EYE_HEIGHT_RATE: f32 = 6.0;
+// Desplazamiento del centro de la órbita por píxel de mouse y por unidad de radio
+const PAN_SPEED: f32 = 0.0015;
+// Con el stick derecho: unidades/s por unidad de radio con el stick a fondo
+const PAN_STICK_SPEED: f32 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    // Gira alrededor de `target`; el botón central mueve el centro
+    Orbit,
+    // WASD en la dirección de la mirada, Q/E baja y sube
+    FreeFly,
+    // Como el vuelo pero caminando sobre el piso
+    FirstPerson,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Orbit => CameraMode::FreeFly,
+            CameraMode::FreeFly => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::Orbit,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "Órbita",
+            CameraMode::FreeFly => "Vuelo libre",
+            CameraMode::FirstPerson => "Primera persona",
+        }
+    }
+}
 
 pub struct CameraControls {
+    pub mode: CameraMode,
+    // Órbita: coordenadas esféricas alrededor de `target`
     pub radius: f32,
     pub yaw: f32,
     pub pitch: f32,
+    pub target: Vector3,
+    // Inercia de la órbita: radianes/s de yaw y pitch, unidades/s de radio
+    yaw_velocity: f32,
+    pitch_velocity: f32,
+    radius_velocity: f32,
+    // Vuelo libre y primera persona: posición y hacia dónde se mira
+    pub position: Vector3,
+    pub look_yaw: f32,
+    pub look_pitch: f32,
+    // Piso por el que se camina en primera persona (altura y medio lado)
+    floor_y: f32,
+    floor_half_size: f32,
 }
 
 impl CameraControls {
@@ -13,98 +89,394 @@ impl CameraControls {
         let yaw = offset.z.atan2(offset.x);
         let pitch = (offset.y / radius).asin();
         
-        Self { radius, yaw, pitch }
+        Self {
+            mode: CameraMode::Orbit,
+            radius,
+            yaw,
+            pitch,
+            target,
+            yaw_velocity: 0.0,
+            pitch_velocity: 0.0,
+            radius_velocity: 0.0,
+            position: initial_position,
+            // Mirando al centro: la dirección opuesta al desplazamiento
+            look_yaw: yaw + PI,
+            look_pitch: -pitch,
+            floor_y: f32::NEG_INFINITY,
+            floor_half_size: f32::INFINITY,
+        }
+    }
+
+    // Piso sobre el que camina la primera persona
+    pub fn set_floor(&mut self, y: f32, size: f32) {
+        self.floor_y = y;
+        self.floor_half_size = size / 2.0;
     }
 
-    pub fn update(&mut self, rl: &RaylibHandle) {
-        // Controles con teclado
-        if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_A) { 
-            self.yaw -= 0.02; 
+    // Cambia de modo conservando la posición y la dirección de la cámara; en primera persona
+    // la altura de los ojos se alcanza de a poco (ver walk)
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        match (self.mode, mode) {
+            (CameraMode::Orbit, CameraMode::FreeFly | CameraMode::FirstPerson) => {
+                self.position = self.orbit_position();
+                self.look_yaw = self.yaw + PI;
+                self.look_pitch = -self.pitch;
+            }
+            (CameraMode::FreeFly | CameraMode::FirstPerson, CameraMode::Orbit) => {
+                // El nuevo centro queda delante de la cámara, a la distancia de la órbita
+                self.target = self.position + self.forward() * self.radius;
+                self.yaw = self.look_yaw - PI;
+                self.pitch = -self.look_pitch;
+            }
+            _ => {}
         }
-        if rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(KeyboardKey::KEY_D) { 
-            self.yaw += 0.02; 
+        // La órbita no sigue girando por inercia al volver a ella
+        self.yaw_velocity = 0.0;
+        self.pitch_velocity = 0.0;
+        self.radius_velocity = 0.0;
+        self.mode = mode;
+    }
+
+    // `dt`: segundos desde el fotograma anterior
+    pub fn update(&mut self, rl: &RaylibHandle, input: &InputMap, dt: f32) {
+        if input.is_pressed(rl, Action::CycleCameraMode) {
+            self.set_mode(self.mode.next());
         }
-        if rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_W) { 
-            self.pitch = (self.pitch + 0.02).clamp(-1.4, 1.4); 
+        match self.mode {
+            CameraMode::Orbit => self.update_orbit(rl, input, dt),
+            CameraMode::FreeFly | CameraMode::FirstPerson => {
+                self.update_look(rl, input, dt);
+                self.update_movement(rl, input, dt);
+            }
         }
-        if rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(KeyboardKey::KEY_S) { 
-            self.pitch = (self.pitch - 0.02).clamp(-1.4, 1.4); 
+    }
+
+    fn update_orbit(&mut self, rl: &RaylibHandle, input: &InputMap, dt: f32) {
+        if input.is_down(rl, Action::Rotate) {
+            // El arrastre mueve la cámara directamente; su velocidad queda como inercia al soltar
+            let mouse_delta = rl.get_mouse_delta() * MOUSE_SENSITIVITY;
+            self.yaw += mouse_delta.x;
+            self.pitch = (self.pitch - mouse_delta.y).clamp(-MAX_PITCH, MAX_PITCH);
+            if dt > 0.0 {
+                self.yaw_velocity = mouse_delta.x / dt;
+                self.pitch_velocity = -mouse_delta.y / dt;
+            }
+        } else {
+            // Teclado y stick izquierdo: fijan la velocidad buscada, la inercia la alcanza
+            let stick = input.stick(rl, Stick::Left);
+            let yaw_input = input.axis(rl, Action::OrbitLeft, Action::OrbitRight) + stick.x;
+            let pitch_input = input.axis(rl, Action::OrbitDown, Action::OrbitUp) + stick.y;
+            let response = smoothing(ORBIT_DAMPING, dt);
+            self.yaw_velocity += (yaw_input * ORBIT_SPEED - self.yaw_velocity) * response;
+            self.pitch_velocity += (pitch_input * ORBIT_SPEED - self.pitch_velocity) * response;
+            self.yaw += self.yaw_velocity * dt;
+            self.pitch += self.pitch_velocity * dt;
+            if self.pitch.abs() >= MAX_PITCH {
+                self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+                self.pitch_velocity = 0.0;
+            }
         }
-        if rl.is_key_down(KeyboardKey::KEY_Q) { 
-            self.radius = (self.radius - 0.08).clamp(2.0, 30.0); 
+
+        // Zoom: Q/E y los gatillos fijan la velocidad buscada, cada paso de la rueda da un impulso
+        // que recorre WHEEL_ZOOM mientras se frena (la integral de v·e^(-k·t) es v/k)
+        let zoom_input = input.axis(rl, Action::ZoomIn, Action::ZoomOut)
+            + input.trigger(rl, Trigger::Right) - input.trigger(rl, Trigger::Left);
+        self.radius_velocity += (zoom_input * ZOOM_SPEED - self.radius_velocity) * smoothing(ZOOM_DAMPING, dt);
+        let wheel_move = rl.get_mouse_wheel_move();
+        if wheel_move != 0.0 {
+            self.radius_velocity -= wheel_move * WHEEL_ZOOM * ZOOM_DAMPING;
+        }
+        self.radius += self.radius_velocity * dt;
+        // Sólo se limita al acercarse o alejarse, una escena puede empezar fuera de los límites
+        if (self.radius < MIN_RADIUS && self.radius_velocity < 0.0) || (self.radius > MAX_RADIUS && self.radius_velocity > 0.0) {
+            self.radius = self.radius.clamp(MIN_RADIUS, MAX_RADIUS);
+            self.radius_velocity = 0.0;
+        }
+
+        // Botón central (arrastrando la escena) o stick derecho (moviendo el centro): en el plano
+        // de la pantalla; con el modificador, sobre el piso
+        let mut pan = input.stick(rl, Stick::Right) * (PAN_STICK_SPEED * self.radius * dt);
+        if input.is_down(rl, Action::Pan) {
+            let mouse_delta = rl.get_mouse_delta() * (PAN_SPEED * self.radius);
+            pan = pan + Vector2::new(-mouse_delta.x, mouse_delta.y);
         }
-        if rl.is_key_down(KeyboardKey::KEY_E) { 
-            self.radius = (self.radius + 0.08).clamp(2.0, 30.0); 
+        if pan.x != 0.0 || pan.y != 0.0 {
+            let forward = (self.target - self.orbit_position()).normalized();
+            let right = forward.cross(Vector3::up()).normalized();
+            let up = if input.is_down(rl, Action::Modifier) {
+                Vector3::new(forward.x, 0.0, forward.z).normalized()
+            } else {
+                right.cross(forward)
+            };
+            self.target = self.target + right * pan.x + up * pan.y;
         }
+    }
 
-        // Control con mouse
-        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
-            let mouse_delta = rl.get_mouse_delta();
-            self.yaw += mouse_delta.x * 0.005;
-            self.pitch = (self.pitch - mouse_delta.y * 0.005).clamp(-1.4, 1.4);
+    // Flechas, stick derecho o click + arrastrar giran la mirada
+    fn update_look(&mut self, rl: &RaylibHandle, input: &InputMap, dt: f32) {
+        let step = ORBIT_SPEED * dt;
+        let stick = input.stick(rl, Stick::Right);
+        self.look_yaw += stick.x * step;
+        self.look_pitch = (self.look_pitch + stick.y * step).clamp(-MAX_PITCH, MAX_PITCH);
+        if input.is_down(rl, Action::LookLeft) {
+            self.look_yaw -= step;
+        }
+        if input.is_down(rl, Action::LookRight) {
+            self.look_yaw += step;
+        }
+        if input.is_down(rl, Action::LookUp) {
+            self.look_pitch = (self.look_pitch + step).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        if input.is_down(rl, Action::LookDown) {
+            self.look_pitch = (self.look_pitch - step).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        if input.is_down(rl, Action::Rotate) {
+            let mouse_delta = rl.get_mouse_delta() * MOUSE_SENSITIVITY;
+            self.look_yaw += mouse_delta.x;
+            self.look_pitch = (self.look_pitch - mouse_delta.y).clamp(-MAX_PITCH, MAX_PITCH);
         }
+    }
+
+    // WASD o stick izquierdo: en vuelo se avanza hacia donde se mira; caminando, sólo en horizontal.
+    // En vuelo los gatillos bajan y suben
+    fn update_movement(&mut self, rl: &RaylibHandle, input: &InputMap, dt: f32) {
+        let walking = self.mode == CameraMode::FirstPerson;
+        let previous = self.position;
+        let forward = if walking {
+            Vector3::new(self.look_yaw.cos(), 0.0, self.look_yaw.sin())
+        } else {
+            self.forward()
+        };
+        let right = forward.cross(Vector3::up()).normalized();
 
-        // Zoom con rueda del mouse
+        let mut motion = Vector3::zero();
+        if input.is_down(rl, Action::MoveForward) {
+            motion = motion + forward;
+        }
+        if input.is_down(rl, Action::MoveBack) {
+            motion = motion - forward;
+        }
+        if input.is_down(rl, Action::MoveRight) {
+            motion = motion + right;
+        }
+        if input.is_down(rl, Action::MoveLeft) {
+            motion = motion - right;
+        }
+        if !walking && input.is_down(rl, Action::MoveUp) {
+            motion = motion + Vector3::up();
+        }
+        if !walking && input.is_down(rl, Action::MoveDown) {
+            motion = motion - Vector3::up();
+        }
+        // Las diagonales con teclas no van más rápido; el stick conserva lo inclinado que esté
+        if motion.length() > 1.0 {
+            motion = motion.normalized();
+        }
+        let stick = input.stick(rl, Stick::Left);
+        motion = motion + forward * stick.y + right * stick.x;
+        if !walking {
+            motion = motion + Vector3::up() * (input.trigger(rl, Trigger::Right) - input.trigger(rl, Trigger::Left));
+        }
+        if motion.length() > 0.0 {
+            let run = input.is_down(rl, Action::Modifier);
+            let base = if walking { WALK_SPEED } else { FLY_SPEED };
+            let speed = if run { base * RUN_MULTIPLIER } else { base };
+            self.position = self.position + motion * (speed * dt);
+        }
+        // La rueda avanza y retrocede en la dirección de la mirada
         let wheel_move = rl.get_mouse_wheel_move();
-        if wheel_move != 0.0 {
-            self.radius = (self.radius - wheel_move * 0.5).clamp(2.0, 30.0);
+        if wheel_move != 0.0 && !walking {
+            self.position = self.position + forward * (wheel_move * WHEEL_ZOOM);
+        }
+
+        if walking {
+            self.walk(previous, dt);
         }
     }
 
-    pub fn update_camera_position(&self, cam: &mut Camera3D) {
+    // Mantiene la primera persona dentro del piso y acerca los ojos a su altura. Si venía de afuera
+    // (al cambiar de modo) el borde se acerca de a poco, como la altura, en vez de saltar en un fotograma
+    fn walk(&mut self, previous: Vector3, dt: f32) {
+        let limit = (self.floor_half_size - WALK_MARGIN).max(0.0);
+        let ease = smoothing(EYE_HEIGHT_RATE, dt);
+        let bound = |before: f32| limit + (before.abs() - limit).max(0.0) * (1.0 - ease);
+        self.position.x = self.position.x.clamp(-bound(previous.x), bound(previous.x));
+        self.position.z = self.position.z.clamp(-bound(previous.z), bound(previous.z));
+        if self.floor_y.is_finite() {
+            let eye_y = self.floor_y + EYE_HEIGHT;
+            self.position.y += (eye_y - self.position.y) * ease;
+        }
+    }
+
+    // Dirección de la mirada en vuelo libre y primera persona
+    fn forward(&self) -> Vector3 {
+        let cp = self.look_pitch.cos();
+        Vector3::new(self.look_yaw.cos() * cp, self.look_pitch.sin(), self.look_yaw.sin() * cp)
+    }
+
+    fn orbit_position(&self) -> Vector3 {
         let cp = self.pitch.cos();
-        cam.position = Vector3::new(
+        Vector3::new(
             self.radius * self.yaw.cos() * cp, 
             self.radius * self.pitch.sin(), 
             self.radius * self.yaw.sin() * cp
-        ) + cam.target;
+        ) + self.target
+    }
+
+    pub fn update_camera_position(&self, cam: &mut Camera3D) {
+        match self.mode {
+            CameraMode::Orbit => {
+                cam.position = self.orbit_position();
+                cam.target = self.target;
+            }
+            CameraMode::FreeFly | CameraMode::FirstPerson => {
+                cam.position = self.position;
+                cam.target = self.position + self.forward();
+            }
+        }
     }
 }
 
-pub struct LightControls {
+// Fracción del camino hacia un valor buscado que se recorre en `dt` segundos al acercarse con
+// tasa `rate` (1/s); no depende de cuántos fotogramas haya en ese tiempo
+fn smoothing(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
+
+// Luces: intensidad por segundo, tono en grados por segundo y giro automático en radianes por segundo
+const INTENSITY_SPEED: f32 = 1.2;
+const HUE_SPEED: f32 = 120.0;
+const LIGHT_ROTATION_SPEED: f32 = 0.6;
+// Giro a mano (modificador + intensidad), radianes por segundo
+const LIGHT_TURN_SPEED: f32 = 1.2;
+
+// Órbita de una luz alrededor del eje Y
+pub struct LightOrbit {
+    pub kind: LightKind,
     pub rotation: f32,
     pub auto_rotate: bool,
     pub radius: f32,
     pub height: f32,
-    pub intensity: f32, // Nueva propiedad para controlar intensidad
+    pub intensity: f32,
+    pub color: [f32; 3], // Color base, se multiplica por la intensidad
+    initial_rotation: f32,
+    initial_direction: Vector3,
 }
 
-impl LightControls {
-    pub fn new() -> Self {
+impl LightOrbit {
+    // La posición inicial define radio, altura y ángulo de la órbita
+    pub fn from_light(light: &Light) -> Self {
+        let [x, y, z] = light.pos;
+        let rotation = z.atan2(x);
         Self {
-            rotation: 0.0,
+            kind: light.kind,
+            rotation,
             auto_rotate: true,
-            radius: 5.0,
-            height: 4.0,
-            intensity: 1.0, // Intensidad por defecto
+            radius: (x * x + z * z).sqrt(),
+            height: y,
+            intensity: 1.0,
+            color: light.color,
+            initial_rotation: rotation,
+            initial_direction: light.direction_vec3(),
         }
     }
 
-    pub fn update(&mut self, rl: &RaylibHandle) {
-        if rl.is_key_pressed(KeyboardKey::KEY_L) { 
-            self.auto_rotate = !self.auto_rotate; 
+    pub fn position(&self) -> Vector3 {
+        Vector3::new(self.radius * self.rotation.cos(), self.height, self.radius * self.rotation.sin())
+    }
+
+    // Lleva la luz a cualquier punto: la órbita pasa a ser el círculo alrededor del eje Y que
+    // pasa por ahí. La dirección de direccionales y focos no cambia
+    pub fn set_position(&mut self, position: Vector3) {
+        let radius = (position.x * position.x + position.z * position.z).sqrt();
+        // Sobre el eje el ángulo no está definido: se conserva
+        if radius > 1e-4 {
+            let rotation = position.z.atan2(position.x);
+            self.initial_rotation += rotation - self.rotation;
+            self.rotation = rotation;
         }
+        self.radius = radius;
+        self.height = position.y;
+    }
+
+    // Gira el tono conservando el brillo; una luz blanca pasa a saturación completa
+    pub fn shift_hue(&mut self, degrees: f32) {
+        let [hue, saturation, value] = colores::rgb_to_hsv(self.color);
+        let saturation = if saturation == 0.0 { 1.0 } else { saturation };
+        self.color = colores::hsv_to_rgb([hue + degrees, saturation, value.max(0.01)]);
+    }
+}
+
+pub struct LightControls {
+    pub orbits: Vec<LightOrbit>,
+    pub selected: usize, // Luz que editan las teclas
+}
+
+impl LightControls {
+    pub fn new(lights: &[Light]) -> Self {
+        Self {
+            orbits: lights.iter().map(LightOrbit::from_light).collect(),
+            selected: 0,
+        }
+    }
 
-        // Controles para intensidad de luz
-        if rl.is_key_down(KeyboardKey::KEY_KP_ADD) || rl.is_key_down(KeyboardKey::KEY_EQUAL) {
-            self.intensity = (self.intensity + 0.02).min(3.0);
+    pub fn selected(&self) -> Option<&LightOrbit> {
+        self.orbits.get(self.selected)
+    }
+
+    pub fn update(&mut self, rl: &RaylibHandle, input: &InputMap, dt: f32) {
+        // Elegir qué luz se edita
+        if input.is_pressed(rl, Action::NextLight) && !self.orbits.is_empty() {
+            self.selected = (self.selected + 1) % self.orbits.len();
         }
-        if rl.is_key_down(KeyboardKey::KEY_KP_SUBTRACT) || rl.is_key_down(KeyboardKey::KEY_MINUS) {
-            self.intensity = (self.intensity - 0.02).max(0.1);
+
+        if let Some(orbit) = self.orbits.get_mut(self.selected) {
+            if input.is_pressed(rl, Action::ToggleLightRotation) { 
+                orbit.auto_rotate = !orbit.auto_rotate; 
+            }
+
+            // Controles para intensidad de luz; con el modificador giran la luz en su órbita
+            let amount = input.axis(rl, Action::IntensityDown, Action::IntensityUp);
+            if input.is_down(rl, Action::Modifier) {
+                orbit.rotation += amount * LIGHT_TURN_SPEED * dt;
+            } else if amount > 0.0 {
+                orbit.intensity = (orbit.intensity + INTENSITY_SPEED * dt).min(3.0);
+            } else if amount < 0.0 {
+                orbit.intensity = (orbit.intensity - INTENSITY_SPEED * dt).max(0.1);
+            }
+
+            // Controles para el tono de la luz
+            if input.is_down(rl, Action::HueLeft) {
+                orbit.shift_hue(-HUE_SPEED * dt);
+            }
+            if input.is_down(rl, Action::HueRight) {
+                orbit.shift_hue(HUE_SPEED * dt);
+            }
+            if input.is_pressed(rl, Action::WhiteLight) {
+                orbit.color = [1.0, 1.0, 1.0]; // Volver a luz blanca
+            }
         }
 
-        if self.auto_rotate {
-            self.rotation += 0.01;
+        for orbit in self.orbits.iter_mut().filter(|o| o.auto_rotate) {
+            orbit.rotation += LIGHT_ROTATION_SPEED * dt;
         }
     }
 
-    pub fn update_light_position(&self, light: &mut crate::light::Light) {
-        light.pos = [
-            self.radius * self.rotation.cos(),
-            self.height,
-            self.radius * self.rotation.sin(),
-        ];
-        // Actualizar intensidad de la luz
-        light.color = [self.intensity, self.intensity, self.intensity];
+    pub fn update_light_positions(&self, lights: &mut [Light]) {
+        for (orbit, light) in self.orbits.iter().zip(lights.iter_mut()) {
+            let pos = orbit.position();
+            light.pos = [pos.x, pos.y, pos.z];
+            // La dirección gira con la órbita (direccionales y focos)
+            let d = rotate_y(orbit.initial_direction, orbit.rotation - orbit.initial_rotation);
+            light.direction = [d.x, d.y, d.z];
+            // Actualizar intensidad de la luz
+            light.color = orbit.color.map(|c| c * orbit.intensity);
+        }
     }
 }
+
+// Rota `v` alrededor del eje Y con el mismo sentido que la órbita (x = cos, z = sin)
+fn rotate_y(v: Vector3, angle: f32) -> Vector3 {
+    let (s, c) = angle.sin_cos();
+    Vector3::new(v.x * c - v.z * s, v.y, v.x * s + v.z * c)
+}
@@ -1,29 +1,86 @@
 use raylib::prelude::*;
 use crate::light;
-use crate::colores;
+use crate::malla::{TrianguloUV, Vertice};
+use crate::material::Material;
+use crate::shaders::LightingShader;
+use crate::transform::Transform;
 
+// Índices de las caras en `face_quads` y en `caras`
+pub const FRONT: usize = 0;
+pub const BACK: usize = 1;
+pub const RIGHT: usize = 2;
+pub const LEFT: usize = 3;
+pub const TOP: usize = 4;
+pub const BOTTOM: usize = 5;
+
+// Lo que se ve en una cara: su propia textura (sin ella, la del material) y la región de la imagen
+// en UV de imagen (origen arriba a la izquierda, 0..1). Las caras laterales quedan derechas vistas
+// desde fuera; la superior tiene su borde de abajo hacia el frente y la inferior su borde de arriba,
+// como al plegar la cruz de `cross_layout`
+#[derive(Clone, Copy)]
+pub struct CaraTextura {
+    pub texture: Option<usize>,
+    pub region: Rectangle,
+}
+
+impl Default for CaraTextura {
+    fn default() -> Self {
+        Self { texture: None, region: Rectangle::new(0.0, 0.0, 1.0, 1.0) }
+    }
+}
+
+impl CaraTextura {
+    // De UV de la cara (v hacia arriba) a UV de la imagen
+    fn map_uv(&self, uv: Vector2) -> Vector2 {
+        Vector2::new(
+            self.region.x + uv.x * self.region.width,
+            self.region.y + (1.0 - uv.y) * self.region.height,
+        )
+    }
+}
+
+// Cruz horizontal estándar de 4x3 celdas:
+//        [+Y]
+//   [-X] [+Z] [+X] [-Z]
+//        [-Y]
+pub fn cross_layout() -> [CaraTextura; 6] {
+    let cell = |col: f32, row: f32| CaraTextura {
+        texture: None,
+        region: Rectangle::new(col / 4.0, row / 3.0, 1.0 / 4.0, 1.0 / 3.0),
+    };
+    let mut caras = [CaraTextura::default(); 6];
+    caras[FRONT] = cell(1.0, 1.0);
+    caras[BACK] = cell(3.0, 1.0);
+    caras[RIGHT] = cell(2.0, 1.0);
+    caras[LEFT] = cell(0.0, 1.0);
+    caras[TOP] = cell(1.0, 0.0);
+    caras[BOTTOM] = cell(1.0, 2.0);
+    caras
+}
+
+// `verts` y las normales de `faces` están en espacio mundo (ya transformados)
 pub struct Cubo {
     pub verts: [Vector3; 8],
     pub faces: [([usize; 3], [usize; 3], Vector3); 6],
     pub size: f32,
-    pub position_offset: f32,
+    pub transform: Transform,
+    pub caras: [CaraTextura; 6], // Textura y región de cada cara, en el orden de `face_quads`
 }
 
 impl Cubo {
-    pub fn new(size: f32, floor_y: f32) -> Self {
+    pub fn new(size: f32, transform: Transform) -> Self {
         let s = size;
-        let cube_y_offset = floor_y + s;
         
         let verts = [
-            Vector3::new(-s, cube_y_offset - s, -s), // 0 - base inferior
-            Vector3::new( s, cube_y_offset - s, -s), // 1
-            Vector3::new( s, cube_y_offset + s, -s), // 2 - base superior
-            Vector3::new(-s, cube_y_offset + s, -s), // 3
-            Vector3::new(-s, cube_y_offset - s,  s), // 4 - base inferior
-            Vector3::new( s, cube_y_offset - s,  s), // 5
-            Vector3::new( s, cube_y_offset + s,  s), // 6 - base superior
-            Vector3::new(-s, cube_y_offset + s,  s), // 7
-        ];
+            Vector3::new(-s, -s, -s), // 0 - base inferior
+            Vector3::new( s, -s, -s), // 1
+            Vector3::new( s,  s, -s), // 2 - base superior
+            Vector3::new(-s,  s, -s), // 3
+            Vector3::new(-s, -s,  s), // 4 - base inferior
+            Vector3::new( s, -s,  s), // 5
+            Vector3::new( s,  s,  s), // 6 - base superior
+            Vector3::new(-s,  s,  s), // 7
+        ].map(|v| transform.transform_point(v));
 
         let faces = [
             // Frente (z+): 4-5-6-7 - orden correcto para CCW desde fuera
@@ -38,36 +95,60 @@ impl Cubo {
             ([3, 6, 7], [3, 2, 6], Vector3::new(0.0, 1.0, 0.0)),
             // Abajo (y-): 0-1-5-4 - orden correcto
             ([0, 5, 4], [0, 1, 5], Vector3::new(0.0, -1.0, 0.0)),
-        ];
+        ].map(|(tri1, tri2, normal)| (tri1, tri2, transform.transform_normal(normal)));
 
         Self {
             verts,
             faces,
             size: s,
-            position_offset: cube_y_offset,
+            transform,
+            caras: [CaraTextura::default(); 6],
         }
     }
 
-    pub fn render_solid_base(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+    pub fn center(&self) -> Vector3 {
+        self.transform.position
+    }
+
+    pub fn render_solid_base(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, material: &Material) {
         // Cubo base extremadamente oscuro para contraste máximo
         let cube_color = Color::new(
-            (colores::OBJECT_COLOR[0] * 15.0) as u8, // Extremadamente oscuro
-            (colores::OBJECT_COLOR[1] * 15.0) as u8,
-            (colores::OBJECT_COLOR[2] * 15.0) as u8,
+            (material.albedo.x * 15.0) as u8, // Extremadamente oscuro
+            (material.albedo.y * 15.0) as u8,
+            (material.albedo.z * 15.0) as u8,
             255
         );
-        d3.draw_cube(Vector3::new(0.0, self.position_offset, 0.0), self.size * 2.0, self.size * 2.0, self.size * 2.0, cube_color);
+        for (tri1, tri2, _) in self.faces.iter() {
+            d3.draw_triangle3D(self.verts[tri1[0]], self.verts[tri1[1]], self.verts[tri1[2]], cube_color);
+            d3.draw_triangle3D(self.verts[tri2[0]], self.verts[tri2[1]], self.verts[tri2[2]], cube_color);
+        }
     }
 
-    pub fn render_lit_faces(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, 
-                           light: &light::Light, cam: &Camera3D, show_normals: bool) {
-        // Crear lista de triángulos para shadow testing
+    // Triángulos con UV en espacio mundo y la textura propia de cada cara.
+    // Las caras son planas: cada vértice lleva la normal de su cara
+    pub fn textured_triangles(&self) -> Vec<TrianguloUV> {
+        let mut triangles = Vec::new();
+        for ((normal, verts), cara) in self.world_quads().iter().zip(&self.caras) {
+            let corner = |i: usize| Vertice { pos: verts[i].0, normal: *normal, uv: verts[i].1 };
+            for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+                triangles.push(TrianguloUV { normal: *normal, verts: [corner(a), corner(b), corner(c)], texture: cara.texture });
+            }
+        }
+        triangles
+    }
+
+    // Lista de triángulos en espacio mundo para shadow testing
+    pub fn triangles(&self) -> Vec<(Vector3, Vector3, Vector3)> {
         let mut all_triangles = Vec::new();
         for (tri1, tri2, _) in self.faces.iter() {
             all_triangles.push((self.verts[tri1[0]], self.verts[tri1[1]], self.verts[tri1[2]]));
             all_triangles.push((self.verts[tri2[0]], self.verts[tri2[1]], self.verts[tri2[2]]));
         }
+        all_triangles
+    }
 
+    pub fn render_lit_faces(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, 
+                           lights: &[light::Light], material: &Material, cam: &Camera3D, show_normals: bool) {
         // Desactivar backface culling completamente para este renderizado
         unsafe { 
             raylib::ffi::rlDisableBackfaceCulling();
@@ -82,7 +163,7 @@ impl Cubo {
             let center1 = (a + b + c) / 3.0;
             
             // Dibujar SIEMPRE, sin importar la orientación
-            let color1 = calculate_realistic_lighting(normal, center1, light, cam.position);
+            let color1 = light::calculate_realistic_lighting(normal, center1, lights, cam.position, material);
             d3.draw_triangle3D(a, b, c, color1);
 
             // Segundo triángulo
@@ -90,7 +171,7 @@ impl Cubo {
             let center2 = (a2 + b2 + c2) / 3.0;
             
             // Dibujar SIEMPRE, sin importar la orientación
-            let color2 = calculate_realistic_lighting(normal, center2, light, cam.position);
+            let color2 = light::calculate_realistic_lighting(normal, center2, lights, cam.position, material);
             d3.draw_triangle3D(a2, b2, c2, color2);
 
             if show_normals {
@@ -145,22 +226,24 @@ impl Cubo {
         }
     }
 
+    // Normal de cada triángulo desde su centro
+    pub fn render_normals(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+        for (tri1, tri2, normal) in self.faces.iter() {
+            for tri in [tri1, tri2] {
+                let ctri = (self.verts[tri[0]] + self.verts[tri[1]] + self.verts[tri[2]]) / 3.0;
+                d3.draw_line_3D(ctri, ctri + *normal * 0.6, Color::YELLOW);
+            }
+        }
+    }
+
     // Versión mejorada para evitar parpadeo de sombras
     pub fn render_shadow_stable(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, 
                                light: &light::Light, floor_y: f32) {
-        let light_pos = light.position_vec3();
-        if light_pos.y > floor_y + 0.1 { // Margen para evitar divisiones por cero
+        if light.lights_plane_from_above(floor_y) {
             let shadow_y = floor_y + 0.0001; // Offset muy pequeño para evitar z-fighting
             
             let shadow_verts: Vec<Vector3> = self.verts.iter()
-                .map(|&v| {
-                    let t = (v.y - shadow_y) / (light_pos.y - shadow_y);
-                    Vector3::new(
-                        v.x - t * (light_pos.x - v.x),
-                        shadow_y,
-                        v.z - t * (light_pos.z - v.z)
-                    )
-                })
+                .map(|&v| light.project_to_plane(v, shadow_y))
                 .collect();
 
             let shadow_color = Color::new(0, 0, 0, 100); // Menos transparencia para mejor visibilidad
@@ -184,124 +267,128 @@ impl Cubo {
         }
     }
 
-    // Renderizar cubo con textura e iluminación
-    pub fn render_textured(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, texture: &Texture2D, light: &light::Light, camera_pos: Vector3) {
+    // Caras del cubo como quads en espacio local: (normal, [(x, y, z, u, v); 4]).
+    // En todas las caras u va hacia la derecha y v hacia arriba, vistas desde fuera
+    pub fn face_quads(&self) -> [(Vector3, [(f32, f32, f32, f32, f32); 4]); 6] {
+        let s = self.size;
+        [
+            // Cara frontal (z+)
+            (Vector3::new(0.0, 0.0, 1.0), [
+                (-s, -s, s, 0.0, 0.0), (s, -s, s, 1.0, 0.0), (s, s, s, 1.0, 1.0), (-s, s, s, 0.0, 1.0)
+            ]),
+            // Cara trasera (z-)
+            (Vector3::new(0.0, 0.0, -1.0), [
+                (-s, -s, -s, 1.0, 0.0), (-s, s, -s, 1.0, 1.0), (s, s, -s, 0.0, 1.0), (s, -s, -s, 0.0, 0.0)
+            ]),
+            // Cara derecha (x+)
+            (Vector3::new(1.0, 0.0, 0.0), [
+                (s, -s, -s, 1.0, 0.0), (s, s, -s, 1.0, 1.0), (s, s, s, 0.0, 1.0), (s, -s, s, 0.0, 0.0)
+            ]),
+            // Cara izquierda (x-)
+            (Vector3::new(-1.0, 0.0, 0.0), [
+                (-s, -s, -s, 0.0, 0.0), (-s, -s, s, 1.0, 0.0), (-s, s, s, 1.0, 1.0), (-s, s, -s, 0.0, 1.0)
+            ]),
+            // Cara superior (y+)
+            (Vector3::new(0.0, 1.0, 0.0), [
+                (-s, s, -s, 0.0, 1.0), (-s, s, s, 0.0, 0.0), (s, s, s, 1.0, 0.0), (s, s, -s, 1.0, 1.0)
+            ]),
+            // Cara inferior (y-)
+            (Vector3::new(0.0, -1.0, 0.0), [
+                (-s, -s, -s, 0.0, 0.0), (s, -s, -s, 1.0, 0.0), (s, -s, s, 1.0, 1.0), (-s, -s, s, 0.0, 1.0)
+            ])
+        ]
+    }
+
+    // Caras del cubo en espacio mundo: (normal, [(posición, uv); 4]), con la UV ya en la región de cada cara
+    pub fn world_quads(&self) -> [(Vector3, [(Vector3, Vector2); 4]); 6] {
+        let quads = self.face_quads();
+        std::array::from_fn(|i| {
+            let (normal, quad) = quads[i];
+            (
+                self.transform.transform_normal(normal),
+                quad.map(|(x, y, z, u, v)| (
+                    self.transform.transform_point(Vector3::new(x, y, z)),
+                    self.caras[i].map_uv(Vector2::new(u, v)),
+                )),
+            )
+        })
+    }
+
+    // Id de la textura de la cara `i`: la suya, la del material o la blanca por defecto
+    fn face_texture_id(&self, i: usize, textures: &[Texture2D], material: &Material) -> u32 {
+        let texture = self.caras[i].texture.or(material.texture).and_then(|t| textures.get(t));
+        texture.map_or_else(|| unsafe { raylib::ffi::rlGetTextureIdDefault() }, |t| t.id)
+    }
+
+    // Renderizar cubo con textura e iluminación (sin textura se usa la textura blanca por defecto).
+    // El color del vértice lleva luz y material; la textura de cada cara lo multiplica al muestrear.
+    pub fn render_textured(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                           lights: &[light::Light], camera_pos: Vector3, material: &Material) {
         // Dibujar cada cara del cubo manualmente con la textura y iluminación realista
         unsafe {
-            raylib::ffi::rlPushMatrix();
-            raylib::ffi::rlTranslatef(0.0, self.position_offset, 0.0);
-            raylib::ffi::rlSetTexture(texture.id);
-            
-            let s = self.size;
-            
             // Iterar sobre cada cara y aplicar iluminación
-            let faces_data = [
-                // Cara frontal (z+)
-                (Vector3::new(0.0, 0.0, 1.0), [
-                    (-s, -s, s, 0.0, 0.0), (s, -s, s, 1.0, 0.0), (s, s, s, 1.0, 1.0), (-s, s, s, 0.0, 1.0)
-                ]),
-                // Cara trasera (z-)
-                (Vector3::new(0.0, 0.0, -1.0), [
-                    (-s, -s, -s, 1.0, 0.0), (-s, s, -s, 1.0, 1.0), (s, s, -s, 0.0, 1.0), (s, -s, -s, 0.0, 0.0)
-                ]),
-                // Cara derecha (x+)
-                (Vector3::new(1.0, 0.0, 0.0), [
-                    (s, -s, -s, 1.0, 0.0), (s, s, -s, 1.0, 1.0), (s, s, s, 0.0, 1.0), (s, -s, s, 0.0, 0.0)
-                ]),
-                // Cara izquierda (x-)
-                (Vector3::new(-1.0, 0.0, 0.0), [
-                    (-s, -s, -s, 0.0, 0.0), (-s, -s, s, 1.0, 0.0), (-s, s, s, 1.0, 1.0), (-s, s, -s, 0.0, 1.0)
-                ]),
-                // Cara superior (y+)
-                (Vector3::new(0.0, 1.0, 0.0), [
-                    (-s, s, -s, 0.0, 1.0), (-s, s, s, 0.0, 0.0), (s, s, s, 1.0, 0.0), (s, s, -s, 1.0, 1.0)
-                ]),
-                // Cara inferior (y-)
-                (Vector3::new(0.0, -1.0, 0.0), [
-                    (-s, -s, -s, 1.0, 1.0), (s, -s, -s, 0.0, 1.0), (s, -s, s, 0.0, 0.0), (-s, -s, s, 1.0, 0.0)
-                ])
-            ];
+            let faces_data = self.world_quads();
             
-            for (normal, vertices) in faces_data.iter() {
+            for (i, (normal, vertices)) in faces_data.iter().enumerate() {
+                raylib::ffi::rlSetTexture(self.face_texture_id(i, textures, material));
+
                 // Calcular el centro de la cara para la iluminación
-                let face_center = Vector3::new(0.0, self.position_offset, 0.0) + *normal * s;
+                let face_center = vertices.iter().fold(Vector3::zero(), |acc, (p, _)| acc + *p) / 4.0;
                 
                 // Calcular iluminación para esta cara
-                let lighting_color = calculate_realistic_lighting(*normal, face_center, light, camera_pos);
-                
-                // Aplicar color de iluminación pero mantener la textura
-                // Mezclar el color de iluminación con blanco para modular la textura
-                let light_factor = lighting_color.r as f32 / 255.0; // Usar canal rojo como intensidad
-                let final_color = Color::new(
-                    (255.0 * light_factor).clamp(50.0, 255.0) as u8,  // Mínimo 50 para que la textura sea visible
-                    (255.0 * light_factor).clamp(50.0, 255.0) as u8,
-                    (255.0 * light_factor).clamp(50.0, 255.0) as u8,
-                    255
-                );
+                let final_color = light::calculate_realistic_lighting(*normal, face_center, lights, camera_pos, material);
                 
                 raylib::ffi::rlColor4ub(final_color.r, final_color.g, final_color.b, final_color.a);
                 raylib::ffi::rlNormal3f(normal.x, normal.y, normal.z);
                 
                 // Dibujar la cara como un quad
                 raylib::ffi::rlBegin(raylib::ffi::RL_QUADS as i32);
-                for (x, y, z, u, v) in vertices.iter() {
-                    raylib::ffi::rlTexCoord2f(*u, *v);
-                    raylib::ffi::rlVertex3f(*x, *y, *z);
+                for (p, uv) in vertices.iter() {
+                    raylib::ffi::rlTexCoord2f(uv.x, uv.y);
+                    raylib::ffi::rlVertex3f(p.x, p.y, p.z);
                 }
                 raylib::ffi::rlEnd();
             }
             
             raylib::ffi::rlSetTexture(0);
-            raylib::ffi::rlPopMatrix();
         }
     }
-}
 
-// Función de iluminación extrema para contraste máximo con color rojo fijo
-fn calculate_realistic_lighting(normal: Vector3, point: Vector3, light: &light::Light, camera_pos: Vector3) -> Color {
-    let light_pos = light.position_vec3();
-    let light_dir = (light_pos - point).normalized();
-    let n = normal.normalized();
-    let view_dir = (camera_pos - point).normalized();
-    
-    // Calcular distancia para atenuación
-    let distance = (light_pos - point).length();
-    let attenuation = 1.0 / (1.0 + 0.05 * distance);
-    
-    // Iluminación difusa con contraste extremo
-    let dot_product = n.dot(light_dir);
-    
-    // Para evitar que las caras desaparezcan, usar el valor absoluto del dot product
-    // Esto hace que ambos lados de la cara reciban algo de iluminación
-    let diffuse_raw = dot_product.abs().max(0.1); // Mínimo 0.1 para visibilidad
-    
-    // Sin luz ambiente prácticamente, solo luz directa
-    let diffuse_intensity = (diffuse_raw * diffuse_raw * attenuation * colores::DIFFUSE_STRENGTH).min(3.0);
-    
-    // Iluminación especular muy brillante
-    let reflect_dir = reflect_vector(-light_dir, n);
-    let spec_factor = view_dir.dot(reflect_dir).max(0.0);
-    let spec_intensity = (spec_factor.powf(32.0) * attenuation * colores::SPECULAR_STRENGTH).min(1.0);
-    
-    // Luz ambiente prácticamente nula pero con mínimo para visibilidad
-    let ambient = colores::AMBIENT_LIGHT.max(0.05); // Mínimo 0.05 para que siempre sea visible
-    
-    // Definir color rojo base fijo
-    let red_base = Color::new(255, 0, 0, 255); // Rojo puro
-    
-    // Combinar componentes manteniendo el color rojo
-    let final_intensity = ambient + diffuse_intensity;
-    let final_specular = spec_intensity;
-    
-    // Aplicar iluminación solo al canal rojo, mantener verde y azul en 0
-    let r = ((red_base.r as f32 * final_intensity * 0.5) + (255.0 * final_specular)).clamp(0.0, 255.0) as u8;
-    let g = (final_specular * 50.0).clamp(0.0, 255.0) as u8; // Pequeña cantidad de verde en reflejos
-    let b = (final_specular * 50.0).clamp(0.0, 255.0) as u8; // Pequeña cantidad de azul en reflejos
-    
-    Color::new(r, g, b, 255)
-}
+    // Renderizar cubo con el shader de iluminación por fragmento (la textura se muestrea en el fragment shader)
+    pub fn render_shaded(&self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                         shader: &mut LightingShader, material: &Material) {
+        shader.set_material(material, textures);
+        unsafe {
+            shader.begin();
+
+            for (i, (normal, vertices)) in self.world_quads().iter().enumerate() {
+                raylib::ffi::rlSetTexture(self.face_texture_id(i, textures, material));
+                raylib::ffi::rlBegin(raylib::ffi::RL_QUADS as i32);
+                raylib::ffi::rlColor4ub(255, 255, 255, 255);
+                raylib::ffi::rlNormal3f(normal.x, normal.y, normal.z);
+                for (p, uv) in vertices.iter() {
+                    raylib::ffi::rlTexCoord2f(uv.x, uv.y);
+                    raylib::ffi::rlVertex3f(p.x, p.y, p.z);
+                }
+                raylib::ffi::rlEnd();
+            }
+
+            raylib::ffi::rlSetTexture(0);
+            shader.end();
+        }
+    }
 
-// Función auxiliar para calcular reflexión
-fn reflect_vector(incident: Vector3, normal: Vector3) -> Vector3 {
-    incident - normal * (2.0 * incident.dot(normal))
+    // Sólo geometría, para la pasada de profundidad del shadow map
+    pub fn render_depth(&self) {
+        unsafe {
+            raylib::ffi::rlBegin(raylib::ffi::RL_TRIANGLES as i32);
+            for (tri1, tri2, _) in self.faces.iter() {
+                for &i in tri1.iter().chain(tri2.iter()) {
+                    let v = self.verts[i];
+                    raylib::ffi::rlVertex3f(v.x, v.y, v.z);
+                }
+            }
+            raylib::ffi::rlEnd();
+        }
+    }
 }
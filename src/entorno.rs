@@ -0,0 +1,280 @@
+use raylib::prelude::*;
+use image::{imageops::{self, FilterType}, Rgb, Rgb32FImage};
+use std::f32::consts::PI;
+use crate::{pbr, shaders};
+
+// Ancho máximo del mapa equirectangular en memoria (el alto es la mitad)
+const MAX_WIDTH: u32 = 2048;
+// Ancho del último mip; los shaders usan la misma cadena (roughness 1 = último mip)
+const MIN_MIP_WIDTH: u32 = 8;
+// Ancho del mip que se integra en armónicos esféricos
+const SH_WIDTH: u32 = 128;
+// Media arista del cubo del skybox (dentro del plano lejano de raylib)
+const SKYBOX_SIZE: f32 = 100.0;
+const SKYBOX_FS: &str = "resources/shaders/skybox.fs";
+
+// Mapa de entorno en memoria: equirectangular en color lineal, con mips para los reflejos rugosos
+// y la irradiancia difusa proyectada en 9 armónicos esféricos. Lo usan el trazador y Skybox
+#[derive(Clone)]
+pub struct Entorno {
+    pub mips: Vec<Rgb32FImage>,
+    pub intensity: f32,
+    pub rotation: f32, // Radianes alrededor de Y
+    pub ibl: bool,     // Ilumina la escena además de verse de fondo
+    pub sh: [Vector3; 9], // Irradiancia (ya convolucionada con el coseno), sin `intensity`
+}
+
+impl Entorno {
+    pub fn new(image: Rgb32FImage, intensity: f32, rotation_degrees: f32, ibl: bool) -> Self {
+        let mut base = image;
+        if base.width() > MAX_WIDTH {
+            base = imageops::resize(&base, MAX_WIDTH, MAX_WIDTH / 2, FilterType::Triangle);
+        }
+        let mut mips = vec![base];
+        loop {
+            let last = &mips[mips.len() - 1];
+            if last.width() / 2 < MIN_MIP_WIDTH {
+                break;
+            }
+            let next = imageops::resize(last, last.width() / 2, (last.height() / 2).max(1), FilterType::Triangle);
+            mips.push(next);
+        }
+        let sh_source = mips.iter().find(|m| m.width() <= SH_WIDTH).unwrap_or(&mips[mips.len() - 1]);
+        let sh = project_sh(sh_source);
+        Self { mips, intensity, rotation: rotation_degrees.to_radians(), ibl, sh }
+    }
+
+    // Nivel más borroso, igual que environmentMaxLod en los shaders
+    pub fn max_lod(&self) -> f32 {
+        (self.mips.len() - 1) as f32
+    }
+
+    // Radiancia que llega desde `dir`
+    pub fn sample(&self, dir: Vector3) -> Vector3 {
+        self.sample_rough(dir, 0.0)
+    }
+
+    // Reflejo prefiltrado: cuanto más rugoso, más borroso es el mip que se lee
+    pub fn sample_rough(&self, dir: Vector3, roughness: f32) -> Vector3 {
+        let lod = (roughness.clamp(0.0, 1.0) * self.max_lod()).round() as usize;
+        let uv = equirect_uv(self.rotate(dir));
+        bilinear(&self.mips[lod], uv) * self.intensity
+    }
+
+    // Irradiancia difusa que recibe una superficie con normal `n` (para la BRDF lambertiana dividir por PI)
+    pub fn irradiance(&self, n: Vector3) -> Vector3 {
+        let basis = sh_basis(self.rotate(n));
+        let e = self.sh.iter().zip(basis).fold(Vector3::zero(), |acc, (c, y)| acc + *c * y);
+        Vector3::new(e.x.max(0.0), e.y.max(0.0), e.z.max(0.0)) * self.intensity
+    }
+
+    // Del mundo al espacio del mapa (mismo giro que rotateEnvironment en los shaders)
+    fn rotate(&self, d: Vector3) -> Vector3 {
+        let (s, c) = (-self.rotation).sin_cos();
+        Vector3::new(d.x * c + d.z * s, d.y, -d.x * s + d.z * c)
+    }
+}
+
+// Imagen equirectangular; .hdr y .exr ya están en lineal, el resto se toma como sRGB
+pub fn load_equirect(path: &str) -> Result<Rgb32FImage, String> {
+    load_linear(path)
+}
+
+// Seis caras en el orden +X, -X, +Y, -Y, +Z, -Z (convención de cubemap de OpenGL),
+// convertidas a equirectangular para tener un único formato
+pub fn load_cubemap(paths: &[String]) -> Result<Rgb32FImage, String> {
+    let faces = paths.iter().map(|p| load_linear(p)).collect::<Result<Vec<_>, _>>()?;
+    let face_size = faces[0].width();
+    let width = (face_size * 4).min(MAX_WIDTH);
+    let height = width / 2;
+    Ok(Rgb32FImage::from_fn(width, height, |x, y| {
+        let dir = equirect_dir((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+        let (face, s, t) = cube_face(dir);
+        let img = &faces[face];
+        let px = ((s * img.width() as f32) as u32).min(img.width() - 1);
+        let py = ((t * img.height() as f32) as u32).min(img.height() - 1);
+        *img.get_pixel(px, py)
+    }))
+}
+
+fn load_linear(path: &str) -> Result<Rgb32FImage, String> {
+    let image = image::open(path).map_err(|e| format!("No se pudo cargar el entorno {}: {}", path, e))?;
+    let is_hdr = path.to_lowercase().ends_with(".hdr") || path.to_lowercase().ends_with(".exr");
+    let mut image = image.to_rgb32f();
+    if !is_hdr {
+        for p in image.pixels_mut() {
+            *p = Rgb(p.0.map(pbr::srgb_to_linear));
+        }
+    }
+    Ok(image)
+}
+
+// Dirección -> UV equirectangular (v = 0 arriba, +Y)
+fn equirect_uv(d: Vector3) -> Vector2 {
+    Vector2::new(0.5 + d.z.atan2(d.x) / (2.0 * PI), d.y.clamp(-1.0, 1.0).acos() / PI)
+}
+
+fn equirect_dir(u: f32, v: f32) -> Vector3 {
+    let phi = (u - 0.5) * 2.0 * PI;
+    let theta = v * PI;
+    Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin())
+}
+
+// Cara del cubemap y coordenadas (s, t) en 0..1 con t = 0 en la fila de arriba
+fn cube_face(d: Vector3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if d.x > 0.0 { (0, -d.z, -d.y, ax) } else { (1, d.z, -d.y, ax) }
+    } else if ay >= az {
+        if d.y > 0.0 { (2, d.x, d.z, ay) } else { (3, d.x, -d.z, ay) }
+    } else if d.z > 0.0 {
+        (4, d.x, -d.y, az)
+    } else {
+        (5, -d.x, -d.y, az)
+    };
+    (face, (sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5)
+}
+
+// Bilineal con repetición en u y bordes fijos en v
+fn bilinear(img: &Rgb32FImage, uv: Vector2) -> Vector3 {
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    let x = uv.x.rem_euclid(1.0) * w - 0.5;
+    let y = (uv.y * h - 0.5).clamp(0.0, h - 1.0);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let texel = |xi: f32, yi: f32| {
+        let p = img.get_pixel(xi.rem_euclid(w) as u32, yi.min(h - 1.0) as u32);
+        Vector3::new(p[0], p[1], p[2])
+    };
+    let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+    let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+// Base de armónicos esféricos de orden 2 (mismo orden que irradianceSH en los shaders)
+fn sh_basis(d: Vector3) -> [f32; 9] {
+    [
+        0.282095,
+        0.488603 * d.y,
+        0.488603 * d.z,
+        0.488603 * d.x,
+        1.092548 * d.x * d.y,
+        1.092548 * d.y * d.z,
+        0.315392 * (3.0 * d.z * d.z - 1.0),
+        1.092548 * d.x * d.z,
+        0.546274 * (d.x * d.x - d.y * d.y),
+    ]
+}
+
+// Proyección de la radiancia en SH y convolución con el coseno (Ramamoorthi y Hanrahan)
+fn project_sh(img: &Rgb32FImage) -> [Vector3; 9] {
+    let (w, h) = (img.width(), img.height());
+    let pixel_area = (2.0 * PI / w as f32) * (PI / h as f32);
+    let mut sh = [Vector3::zero(); 9];
+    for (x, y, p) in img.enumerate_pixels() {
+        let (u, v) = ((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+        let solid_angle = pixel_area * (v * PI).sin();
+        let radiance = Vector3::new(p[0], p[1], p[2]) * solid_angle;
+        for (c, basis) in sh.iter_mut().zip(sh_basis(equirect_dir(u, v))) {
+            *c = *c + radiance * basis;
+        }
+    }
+    let band = [PI, 2.0 * PI / 3.0, 2.0 * PI / 3.0, 2.0 * PI / 3.0, PI / 4.0, PI / 4.0, PI / 4.0, PI / 4.0, PI / 4.0];
+    for (c, a) in sh.iter_mut().zip(band) {
+        *c = *c * a;
+    }
+    sh
+}
+
+// Lado GPU del entorno: la textura equirectangular (float, con mipmaps) y el shader del fondo
+pub struct Skybox {
+    shader: Shader,
+    texture: Texture2D,
+    view_pos_loc: i32,
+    model_loc: i32,
+    pub intensity: f32,
+    pub rotation: f32,
+    pub ibl: bool,
+    pub sh: [Vector3; 9],
+    pub max_lod: f32,
+}
+
+impl Skybox {
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, entorno: &Entorno) -> Self {
+        let base = &entorno.mips[0];
+        let mut data = base.as_raw().clone();
+        let texture = unsafe {
+            // LoadTextureFromImage copia los datos a la GPU; `data` sólo tiene que vivir hasta entonces
+            let image = raylib::ffi::Image {
+                data: data.as_mut_ptr() as *mut std::ffi::c_void,
+                width: base.width() as i32,
+                height: base.height() as i32,
+                mipmaps: 1,
+                format: raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R32G32B32 as i32,
+            };
+            let mut raw = raylib::ffi::LoadTextureFromImage(image);
+            raylib::ffi::GenTextureMipmaps(&mut raw);
+            raylib::ffi::SetTextureFilter(raw, raylib::ffi::TextureFilter::TEXTURE_FILTER_TRILINEAR as i32);
+            // Se repite en u (la costura de atan2) pero no en v (los polos)
+            raylib::ffi::rlTextureParameters(raw.id, raylib::ffi::RL_TEXTURE_WRAP_T as i32, raylib::ffi::RL_TEXTURE_WRAP_CLAMP as i32);
+            Texture2D::from_raw(raw)
+        };
+
+        let mut shader = rl.load_shader(thread, Some(shaders::LIGHTING_VS), Some(SKYBOX_FS));
+        let intensity_loc = shader.get_shader_location("environmentIntensity");
+        let rotation_loc = shader.get_shader_location("environmentRotation");
+        shader.set_shader_value(intensity_loc, entorno.intensity);
+        shader.set_shader_value(rotation_loc, entorno.rotation);
+
+        Self {
+            view_pos_loc: shader.get_shader_location("viewPos"),
+            model_loc: shader.get_shader_location("matModel"),
+            shader,
+            texture,
+            intensity: entorno.intensity,
+            rotation: entorno.rotation,
+            ibl: entorno.ibl,
+            sh: entorno.sh,
+            // La cadena de la GPU llega a 1x1, pero se corta en el mismo nivel que la de CPU
+            max_lod: entorno.max_lod(),
+        }
+    }
+
+    pub fn texture_id(&self) -> u32 {
+        self.texture.id
+    }
+
+    // Cubo centrado en la cámara, sin escribir profundidad: todo lo demás se dibuja encima
+    pub fn render(&mut self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, cam: &Camera3D) {
+        self.shader.set_shader_value(self.view_pos_loc, cam.position);
+        self.shader.set_shader_value_matrix(self.model_loc, Matrix::identity());
+        let c = cam.position;
+        let s = SKYBOX_SIZE;
+        let corners = [
+            Vector3::new(-s, -s, -s), Vector3::new(s, -s, -s), Vector3::new(s, s, -s), Vector3::new(-s, s, -s),
+            Vector3::new(-s, -s, s), Vector3::new(s, -s, s), Vector3::new(s, s, s), Vector3::new(-s, s, s),
+        ];
+        let quads = [[4, 5, 6, 7], [1, 0, 3, 2], [5, 1, 2, 6], [0, 4, 7, 3], [3, 7, 6, 2], [0, 1, 5, 4]];
+        unsafe {
+            raylib::ffi::rlDisableBackfaceCulling();
+            raylib::ffi::rlDisableDepthMask();
+            raylib::ffi::BeginShaderMode(*self.shader.as_ref());
+            raylib::ffi::rlSetTexture(self.texture.id);
+            raylib::ffi::rlBegin(raylib::ffi::RL_QUADS as i32);
+            raylib::ffi::rlColor4ub(255, 255, 255, 255);
+            for quad in quads {
+                for i in quad {
+                    let p = c + corners[i];
+                    raylib::ffi::rlVertex3f(p.x, p.y, p.z);
+                }
+            }
+            raylib::ffi::rlEnd();
+            raylib::ffi::rlSetTexture(0);
+            raylib::ffi::EndShaderMode();
+            raylib::ffi::rlEnableDepthMask();
+            raylib::ffi::rlEnableBackfaceCulling();
+        }
+    }
+}
+
+// Módulo para el mapa de entorno (skybox e iluminación basada en imagen)
@@ -0,0 +1,457 @@
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Archivo de controles que se carga si existe (se puede cambiar con --controls)
+pub const CONTROLS_PATH: &str = "controles.toml";
+
+// Lo que hace el usuario, independiente de la tecla o botón que lo dispara.
+// En el archivo de controles se escriben en snake_case: `orbit_left = ["left", "a"]`
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    // Cámara en órbita
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    ZoomIn,
+    ZoomOut,
+    Rotate, // Mantener y arrastrar el mouse (órbita y mirada)
+    Pan,    // Mantener y arrastrar: mueve el centro de la órbita
+    CycleCameraMode,
+    // Vuelo libre y primera persona
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    // Mantener: corre, mueve el centro sobre el piso, hace la captura en EXR, cambia
+    // la intensidad de la luz por su giro y el PCF por la resolución de las sombras
+    Modifier,
+    // Luces
+    NextLight,
+    ToggleLightRotation,
+    IntensityUp,
+    IntensityDown,
+    HueLeft,
+    HueRight,
+    WhiteLight,
+    Grab,        // Click sobre una luz o el gizmo para arrastrarla
+    ToggleGizmo,
+    // Render y pantalla
+    ToggleNormals,
+    ToggleWireframe,
+    ToggleUi,
+    CycleRenderMode,
+    PcfUp,
+    PcfDown,
+    CycleTonemapper,
+    ExposureUp,
+    ExposureDown,
+    ToggleBloom,
+    ToggleDepthOfField,
+    ToggleFxaa,
+    ToggleColorGrading,
+    ToggleChromaticAberration,
+    ToggleVignette,
+    Screenshot,
+    ToggleRecording,
+}
+
+// Controles por defecto; el archivo de controles reemplaza los de las acciones que nombra
+const DEFAULT_BINDINGS: &[(Action, &[&str])] = &[
+    (Action::OrbitLeft, &["left", "a"]),
+    (Action::OrbitRight, &["right", "d"]),
+    (Action::OrbitUp, &["up", "w"]),
+    (Action::OrbitDown, &["down", "s"]),
+    (Action::ZoomIn, &["q"]),
+    (Action::ZoomOut, &["e"]),
+    (Action::Rotate, &["mouse_left"]),
+    (Action::Pan, &["mouse_middle"]),
+    (Action::CycleCameraMode, &["c", "gamepad_north"]),
+    (Action::MoveForward, &["w"]),
+    (Action::MoveBack, &["s"]),
+    (Action::MoveLeft, &["a"]),
+    (Action::MoveRight, &["d"]),
+    (Action::MoveUp, &["e"]),
+    (Action::MoveDown, &["q"]),
+    (Action::LookLeft, &["left"]),
+    (Action::LookRight, &["right"]),
+    (Action::LookUp, &["up"]),
+    (Action::LookDown, &["down"]),
+    (Action::Modifier, &["left_shift", "right_shift", "gamepad_west"]),
+    (Action::NextLight, &["tab", "gamepad_dpad_right"]),
+    (Action::ToggleLightRotation, &["l", "gamepad_south"]),
+    (Action::IntensityUp, &["equal", "kp_add", "gamepad_right_bumper"]),
+    (Action::IntensityDown, &["minus", "kp_subtract", "gamepad_left_bumper"]),
+    (Action::HueLeft, &["h"]),
+    (Action::HueRight, &["j"]),
+    (Action::WhiteLight, &["k"]),
+    (Action::Grab, &["mouse_left"]),
+    (Action::ToggleGizmo, &["x"]),
+    (Action::ToggleNormals, &["n"]),
+    (Action::ToggleWireframe, &["b"]),
+    (Action::ToggleUi, &["u", "gamepad_select"]),
+    (Action::CycleRenderMode, &["m", "gamepad_east"]),
+    (Action::PcfUp, &["right_bracket"]),
+    (Action::PcfDown, &["left_bracket"]),
+    (Action::CycleTonemapper, &["t"]),
+    (Action::ExposureUp, &["period"]),
+    (Action::ExposureDown, &["comma"]),
+    (Action::ToggleBloom, &["f1"]),
+    (Action::ToggleDepthOfField, &["f2"]),
+    (Action::ToggleFxaa, &["f3"]),
+    (Action::ToggleColorGrading, &["f4"]),
+    (Action::ToggleChromaticAberration, &["f5"]),
+    (Action::ToggleVignette, &["f6"]),
+    (Action::Screenshot, &["p"]),
+    (Action::ToggleRecording, &["g"]),
+];
+
+// Una tecla, botón del mouse o botón del gamepad (el primero conectado)
+#[derive(Clone, Copy, PartialEq)]
+pub enum Input {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+// Nombre en el archivo de controles, texto de la ayuda y entrada
+const INPUT_NAMES: &[(&str, &str, Input)] = &[
+    ("a", "A", Input::Key(KeyboardKey::KEY_A)), ("b", "B", Input::Key(KeyboardKey::KEY_B)),
+    ("c", "C", Input::Key(KeyboardKey::KEY_C)), ("d", "D", Input::Key(KeyboardKey::KEY_D)),
+    ("e", "E", Input::Key(KeyboardKey::KEY_E)), ("f", "F", Input::Key(KeyboardKey::KEY_F)),
+    ("g", "G", Input::Key(KeyboardKey::KEY_G)), ("h", "H", Input::Key(KeyboardKey::KEY_H)),
+    ("i", "I", Input::Key(KeyboardKey::KEY_I)), ("j", "J", Input::Key(KeyboardKey::KEY_J)),
+    ("k", "K", Input::Key(KeyboardKey::KEY_K)), ("l", "L", Input::Key(KeyboardKey::KEY_L)),
+    ("m", "M", Input::Key(KeyboardKey::KEY_M)), ("n", "N", Input::Key(KeyboardKey::KEY_N)),
+    ("o", "O", Input::Key(KeyboardKey::KEY_O)), ("p", "P", Input::Key(KeyboardKey::KEY_P)),
+    ("q", "Q", Input::Key(KeyboardKey::KEY_Q)), ("r", "R", Input::Key(KeyboardKey::KEY_R)),
+    ("s", "S", Input::Key(KeyboardKey::KEY_S)), ("t", "T", Input::Key(KeyboardKey::KEY_T)),
+    ("u", "U", Input::Key(KeyboardKey::KEY_U)), ("v", "V", Input::Key(KeyboardKey::KEY_V)),
+    ("w", "W", Input::Key(KeyboardKey::KEY_W)), ("x", "X", Input::Key(KeyboardKey::KEY_X)),
+    ("y", "Y", Input::Key(KeyboardKey::KEY_Y)), ("z", "Z", Input::Key(KeyboardKey::KEY_Z)),
+    ("0", "0", Input::Key(KeyboardKey::KEY_ZERO)), ("1", "1", Input::Key(KeyboardKey::KEY_ONE)),
+    ("2", "2", Input::Key(KeyboardKey::KEY_TWO)), ("3", "3", Input::Key(KeyboardKey::KEY_THREE)),
+    ("4", "4", Input::Key(KeyboardKey::KEY_FOUR)), ("5", "5", Input::Key(KeyboardKey::KEY_FIVE)),
+    ("6", "6", Input::Key(KeyboardKey::KEY_SIX)), ("7", "7", Input::Key(KeyboardKey::KEY_SEVEN)),
+    ("8", "8", Input::Key(KeyboardKey::KEY_EIGHT)), ("9", "9", Input::Key(KeyboardKey::KEY_NINE)),
+    ("f1", "F1", Input::Key(KeyboardKey::KEY_F1)), ("f2", "F2", Input::Key(KeyboardKey::KEY_F2)),
+    ("f3", "F3", Input::Key(KeyboardKey::KEY_F3)), ("f4", "F4", Input::Key(KeyboardKey::KEY_F4)),
+    ("f5", "F5", Input::Key(KeyboardKey::KEY_F5)), ("f6", "F6", Input::Key(KeyboardKey::KEY_F6)),
+    ("f7", "F7", Input::Key(KeyboardKey::KEY_F7)), ("f8", "F8", Input::Key(KeyboardKey::KEY_F8)),
+    ("f9", "F9", Input::Key(KeyboardKey::KEY_F9)), ("f10", "F10", Input::Key(KeyboardKey::KEY_F10)),
+    ("f11", "F11", Input::Key(KeyboardKey::KEY_F11)), ("f12", "F12", Input::Key(KeyboardKey::KEY_F12)),
+    ("left", "Izq", Input::Key(KeyboardKey::KEY_LEFT)), ("right", "Der", Input::Key(KeyboardKey::KEY_RIGHT)),
+    ("up", "Arriba", Input::Key(KeyboardKey::KEY_UP)), ("down", "Abajo", Input::Key(KeyboardKey::KEY_DOWN)),
+    ("space", "Espacio", Input::Key(KeyboardKey::KEY_SPACE)), ("tab", "TAB", Input::Key(KeyboardKey::KEY_TAB)),
+    ("enter", "Enter", Input::Key(KeyboardKey::KEY_ENTER)), ("backspace", "Borrar", Input::Key(KeyboardKey::KEY_BACKSPACE)),
+    ("left_shift", "Shift", Input::Key(KeyboardKey::KEY_LEFT_SHIFT)), ("right_shift", "Shift", Input::Key(KeyboardKey::KEY_RIGHT_SHIFT)),
+    ("left_control", "Ctrl", Input::Key(KeyboardKey::KEY_LEFT_CONTROL)), ("right_control", "Ctrl", Input::Key(KeyboardKey::KEY_RIGHT_CONTROL)),
+    ("left_alt", "Alt", Input::Key(KeyboardKey::KEY_LEFT_ALT)), ("right_alt", "Alt", Input::Key(KeyboardKey::KEY_RIGHT_ALT)),
+    ("equal", "+", Input::Key(KeyboardKey::KEY_EQUAL)), ("minus", "-", Input::Key(KeyboardKey::KEY_MINUS)),
+    ("kp_add", "+ num", Input::Key(KeyboardKey::KEY_KP_ADD)), ("kp_subtract", "- num", Input::Key(KeyboardKey::KEY_KP_SUBTRACT)),
+    ("comma", ",", Input::Key(KeyboardKey::KEY_COMMA)), ("period", ".", Input::Key(KeyboardKey::KEY_PERIOD)),
+    ("left_bracket", "[", Input::Key(KeyboardKey::KEY_LEFT_BRACKET)), ("right_bracket", "]", Input::Key(KeyboardKey::KEY_RIGHT_BRACKET)),
+    ("slash", "/", Input::Key(KeyboardKey::KEY_SLASH)), ("semicolon", ";", Input::Key(KeyboardKey::KEY_SEMICOLON)),
+    ("mouse_left", "Click izq", Input::Mouse(MouseButton::MOUSE_BUTTON_LEFT)),
+    ("mouse_right", "Click der", Input::Mouse(MouseButton::MOUSE_BUTTON_RIGHT)),
+    ("mouse_middle", "Botón central", Input::Mouse(MouseButton::MOUSE_BUTTON_MIDDLE)),
+    // Botones por posición (en Xbox: A, B, X, Y)
+    ("gamepad_south", "Pad A", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)),
+    ("gamepad_east", "Pad B", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)),
+    ("gamepad_west", "Pad X", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT)),
+    ("gamepad_north", "Pad Y", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP)),
+    ("gamepad_dpad_up", "Cruceta arriba", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)),
+    ("gamepad_dpad_down", "Cruceta abajo", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN)),
+    ("gamepad_dpad_left", "Cruceta izq", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)),
+    ("gamepad_dpad_right", "Cruceta der", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)),
+    ("gamepad_left_bumper", "LB", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1)),
+    ("gamepad_right_bumper", "RB", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1)),
+    ("gamepad_left_trigger", "LT", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2)),
+    ("gamepad_right_trigger", "RT", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)),
+    ("gamepad_select", "Select", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT)),
+    ("gamepad_start", "Start", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT)),
+    ("gamepad_left_stick", "L3", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB)),
+    ("gamepad_right_stick", "R3", Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB)),
+];
+
+// Gamepad que se lee (el primero conectado)
+const GAMEPAD: i32 = 0;
+
+// Sticks y gatillos del gamepad, en la tabla `[gamepad]` del archivo de controles
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamepadSettings {
+    pub deadzone: f32,            // Inclinación del stick (0..1) que se ignora
+    pub trigger_deadzone: f32,    // Recorrido del gatillo (0..1) que se ignora
+    pub stick_sensitivity: f32,   // Multiplica la velocidad de giro y de movimiento
+    pub trigger_sensitivity: f32, // Multiplica la velocidad del zoom
+    pub invert_y: bool,           // Stick hacia arriba mira hacia abajo
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self { deadzone: 0.15, trigger_deadzone: 0.05, stick_sensitivity: 1.0, trigger_sensitivity: 1.0, invert_y: false }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy)]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+// Formato del archivo de controles: acciones sueltas y la tabla [gamepad]
+#[derive(Deserialize)]
+struct ControlsFile {
+    #[serde(default)]
+    gamepad: GamepadSettings,
+    #[serde(flatten)]
+    bindings: HashMap<Action, Vec<String>>,
+}
+
+// Quita la zona muerta y estira el resto para que el valor vuelva a cubrir 0..1
+fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value <= deadzone {
+        0.0
+    } else {
+        ((value - deadzone) / (1.0 - deadzone).max(f32::EPSILON)).min(1.0)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Binding {
+    pub input: Input,
+    pub label: &'static str, // Cómo se muestra en la ayuda
+}
+
+impl Binding {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim().to_lowercase();
+        INPUT_NAMES.iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|&(_, label, input)| Binding { input, label })
+            .ok_or_else(|| format!("Entrada desconocida: \"{}\"", name))
+    }
+
+    fn is_down(&self, rl: &RaylibHandle) -> bool {
+        match self.input {
+            Input::Key(key) => rl.is_key_down(key),
+            Input::Mouse(button) => rl.is_mouse_button_down(button),
+            Input::Gamepad(button) => rl.is_gamepad_available(GAMEPAD) && rl.is_gamepad_button_down(GAMEPAD, button),
+        }
+    }
+
+    fn is_pressed(&self, rl: &RaylibHandle) -> bool {
+        match self.input {
+            Input::Key(key) => rl.is_key_pressed(key),
+            Input::Mouse(button) => rl.is_mouse_button_pressed(button),
+            Input::Gamepad(button) => rl.is_gamepad_available(GAMEPAD) && rl.is_gamepad_button_pressed(GAMEPAD, button),
+        }
+    }
+}
+
+// Qué entradas disparan cada acción
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+    pub gamepad: GamepadSettings,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS.iter()
+            .map(|&(action, names)| {
+                let bindings = names.iter()
+                    .map(|name| Binding::parse(name).expect("Control por defecto inválido"))
+                    .collect();
+                (action, bindings)
+            })
+            .collect();
+        Self { bindings, gamepad: GamepadSettings::default() }
+    }
+}
+
+impl InputMap {
+    // Controles por defecto con los del archivo encima. Ejemplo (ver controles.toml):
+    //   toggle_wireframe = ["b", "gamepad_north"]
+    //   zoom_in = []   # sin asignar
+    //   [gamepad]
+    //   deadzone = 0.2
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer los controles {}: {}", path, e))?;
+        Self::parse(&text, path)
+    }
+
+    // `path` sólo aparece en los mensajes de error
+    fn parse(text: &str, path: &str) -> Result<Self, String> {
+        let file: ControlsFile = toml::from_str(text)
+            .map_err(|e| format!("Controles inválidos {}: {}", path, e))?;
+        let mut map = Self { gamepad: file.gamepad, ..Self::default() };
+        for (action, names) in file.bindings {
+            let bindings = names.iter()
+                .map(|name| Binding::parse(name))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Controles inválidos {} ({:?}): {}", path, action, e))?;
+            map.bindings.insert(action, bindings);
+        }
+        Ok(map)
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Alguna de sus entradas está presionada
+    pub fn is_down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.bindings(action).iter().any(|b| b.is_down(rl))
+    }
+
+    // Alguna de sus entradas se presionó en este fotograma
+    pub fn is_pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.bindings(action).iter().any(|b| b.is_pressed(rl))
+    }
+
+    // -1, 0 o 1 según cuál de las dos acciones está presionada
+    pub fn axis(&self, rl: &RaylibHandle, negative: Action, positive: Action) -> f32 {
+        self.is_down(rl, positive) as i32 as f32 - self.is_down(rl, negative) as i32 as f32
+    }
+
+    // Inclinación de un stick con la zona muerta y la sensibilidad aplicadas; `y` positivo es
+    // hacia arriba (al revés que raylib) salvo con invert_y. Sin gamepad, cero
+    pub fn stick(&self, rl: &RaylibHandle, stick: Stick) -> Vector2 {
+        if !rl.is_gamepad_available(GAMEPAD) {
+            return Vector2::zero();
+        }
+        let (axis_x, axis_y) = match stick {
+            Stick::Left => (GamepadAxis::GAMEPAD_AXIS_LEFT_X, GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+            Stick::Right => (GamepadAxis::GAMEPAD_AXIS_RIGHT_X, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+        };
+        let y_sign = if self.gamepad.invert_y { 1.0 } else { -1.0 };
+        let raw = Vector2::new(rl.get_gamepad_axis_movement(GAMEPAD, axis_x),
+                               y_sign * rl.get_gamepad_axis_movement(GAMEPAD, axis_y));
+        // Zona muerta radial: no frena las diagonales como una por eje
+        let length = raw.length();
+        if length <= self.gamepad.deadzone {
+            return Vector2::zero();
+        }
+        raw * (apply_deadzone(length, self.gamepad.deadzone) / length * self.gamepad.stick_sensitivity)
+    }
+
+    // Cuánto está apretado un gatillo (0..1, por la sensibilidad)
+    pub fn trigger(&self, rl: &RaylibHandle, trigger: Trigger) -> f32 {
+        if !rl.is_gamepad_available(GAMEPAD) {
+            return 0.0;
+        }
+        let axis = match trigger {
+            Trigger::Left => GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER,
+            Trigger::Right => GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER,
+        };
+        // raylib da -1 suelto y 1 apretado a fondo
+        let pressed = (rl.get_gamepad_axis_movement(GAMEPAD, axis) + 1.0) / 2.0;
+        apply_deadzone(pressed, self.gamepad.trigger_deadzone) * self.gamepad.trigger_sensitivity
+    }
+
+    // Texto de la ayuda: "Izq/A" (Shift izquierdo y derecho se muestran una vez)
+    pub fn describe(&self, action: Action) -> String {
+        let mut labels: Vec<&str> = Vec::new();
+        for binding in self.bindings(action) {
+            if !labels.contains(&binding.label) {
+                labels.push(binding.label);
+            }
+        }
+        labels.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(map: &InputMap, action: Action) -> Vec<Input> {
+        map.bindings(action).iter().map(|b| b.input).collect()
+    }
+
+    #[test]
+    fn binding_parse_ignores_case_and_spaces() {
+        let binding = Binding::parse(" Left_Shift ").unwrap();
+        assert!(binding.input == Input::Key(KeyboardKey::KEY_LEFT_SHIFT));
+        assert_eq!(binding.label, "Shift");
+        assert!(Binding::parse("mouse_middle").unwrap().input == Input::Mouse(MouseButton::MOUSE_BUTTON_MIDDLE));
+        assert!(Binding::parse("gamepad_south").unwrap().input == Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN));
+    }
+
+    #[test]
+    fn binding_parse_rejects_unknown_names() {
+        let error = Binding::parse("tecla_rara").err().unwrap();
+        assert!(error.contains("tecla_rara"), "{}", error);
+        assert!(Binding::parse("").is_err());
+    }
+
+    #[test]
+    fn every_default_binding_parses() {
+        // InputMap::default hace expect sobre cada nombre de DEFAULT_BINDINGS
+        let map = InputMap::default();
+        for (action, names) in DEFAULT_BINDINGS {
+            assert_eq!(map.bindings(*action).len(), names.len(), "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn parse_replaces_only_the_named_actions() {
+        let text = "\
+zoom_in = [\"z\", \"gamepad_north\"]
+screenshot = []
+
+[gamepad]
+deadzone = 0.3
+invert_y = true
+";
+        let map = InputMap::parse(text, "controles.toml").unwrap();
+        assert!(inputs(&map, Action::ZoomIn) == vec![Input::Key(KeyboardKey::KEY_Z),
+                                                     Input::Gamepad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP)]);
+        assert!(map.bindings(Action::Screenshot).is_empty());
+        // Las que no nombra quedan como por defecto
+        assert!(inputs(&map, Action::ZoomOut) == inputs(&InputMap::default(), Action::ZoomOut));
+        assert_eq!((map.gamepad.deadzone, map.gamepad.invert_y), (0.3, true));
+        assert_eq!(map.gamepad.trigger_deadzone, GamepadSettings::default().trigger_deadzone);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_binding_names() {
+        let error = InputMap::parse("zoom_in = [\"z\", \"tecla_rara\"]", "controles.toml").err().unwrap();
+        assert!(error.contains("ZoomIn") && error.contains("tecla_rara"), "{}", error);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_actions_and_gamepad_fields() {
+        assert!(InputMap::parse("saltar = [\"space\"]", "controles.toml").is_err());
+        assert!(InputMap::parse("[gamepad]\ndeadzon = 0.2", "controles.toml").is_err());
+    }
+
+    #[test]
+    fn apply_deadzone_zeroes_inside_and_rescales_outside() {
+        assert_eq!(apply_deadzone(0.0, 0.15), 0.0);
+        assert_eq!(apply_deadzone(0.15, 0.15), 0.0);
+        assert!((apply_deadzone(0.575, 0.15) - 0.5).abs() < 1e-6);
+        assert_eq!(apply_deadzone(1.0, 0.15), 1.0);
+        // Sin zona muerta no cambia; con zona muerta total no divide por cero
+        assert_eq!(apply_deadzone(0.4, 0.0), 0.4);
+        assert_eq!(apply_deadzone(1.0, 1.0), 0.0);
+    }
+}
+
+// Módulo para el mapa de entradas (acciones y sus teclas, botones del mouse y del gamepad)
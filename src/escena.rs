@@ -1,43 +1,175 @@
 use raylib::prelude::*;
-use crate::{cubo::Cubo, piso::Piso, light::Light};
+use crate::{objeto::Objeto, piso::Piso, light::Light, shaders::LightingShader, archivo_escena::SceneFile, entorno::Entorno};
+use crate::{posproceso::PostSettings, shaders::VertexColorShader, tonos::Tonemap};
+
+// Resolución del piso teselado en el modo trazado; con muchos triángulos baja para que
+// celdas x triángulos no pase de RAYTRACED_FLOOR_BUDGET (cada celda traza contra todos)
+const RAYTRACED_FLOOR_RESOLUTION: usize = 64;
+const RAYTRACED_FLOOR_MIN_RESOLUTION: usize = 8;
+const RAYTRACED_FLOOR_BUDGET: usize = 64 * 64 * 64;
+// Resolución del piso iluminado por celdas en el modo CPU por cara
+const CPU_FLOOR_RESOLUTION: usize = 24;
+
+// Cómo se ilumina la escena en tiempo real
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    // Phong por fragmento con lighting.vs / lighting.fs
+    Shader,
+    // Cook-Torrance GGX (metallic / roughness) con lighting.vs / pbr.fs
+    Pbr,
+    // Un color por cara calculado en CPU (fallback)
+    CpuPerFace,
+    // Piso teselado con sombras trazadas por rayos, más la sombra proyectada encima para comparar
+    RayTraced,
+}
+
+impl RenderMode {
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::Shader => RenderMode::Pbr,
+            RenderMode::Pbr => RenderMode::CpuPerFace,
+            RenderMode::CpuPerFace => RenderMode::RayTraced,
+            RenderMode::RayTraced => RenderMode::Shader,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RenderMode::Shader => "Shader",
+            RenderMode::Pbr => "PBR",
+            RenderMode::CpuPerFace => "CPU por cara",
+            RenderMode::RayTraced => "Sombras trazadas",
+        }
+    }
+
+    // Modos que dibujan con un shader por fragmento (y usan shadow maps)
+    pub fn uses_shader(self) -> bool {
+        matches!(self, RenderMode::Shader | RenderMode::Pbr)
+    }
+}
+
+// Cómo se dibuja un fotograma: modo, shaders y superposiciones (se arma en cada fotograma)
+pub struct RenderOptions<'a> {
+    pub mode: RenderMode,
+    pub shader: &'a mut LightingShader, // Phong o PBR según el modo
+    pub vertex_shader: &'a VertexColorShader, // Modos por CPU: colores de vértice en HDR
+    pub show_normals: bool,
+    pub show_wireframe: bool,
+}
 
 pub struct Escena {
-    pub cubo: Cubo,
+    pub objetos: Vec<Objeto>,
     pub piso: Piso,
+    pub lights: Vec<Light>,
     pub background_color: Color,
-    pub texture: Texture2D,
+    pub entorno: Option<Entorno>, // Fondo e iluminación basada en imagen
+    pub tonemap: Tonemap, // Exposición y operador de tono de la imagen final
+    pub post: PostSettings, // Efectos entre el framebuffer y la pantalla (sólo en la ventana)
+    pub textures: Vec<Texture2D>,
 }
 
 impl Escena {
-    pub fn new_with_texture(texture: Texture2D) -> Self {
-        let floor_y = -1.5;
-        let floor_size = 8.0;
-        let cube_size = 1.0;
+    // Las texturas siguen el orden de SceneFile::texture_paths; sin ventana (trazador por CPU) se pasa un Vec vacío
+    pub fn from_description(desc: &SceneFile, textures: Vec<Texture2D>) -> Self {
+        let paths = desc.texture_paths();
         Self {
-            cubo: Cubo::new(cube_size, floor_y),
-            piso: Piso::new(floor_y, floor_size),
-            background_color: Color::new(20, 25, 35, 255),
-            texture,
+            objetos: desc.objects.iter().flat_map(|o| o.build(desc.floor.y, &paths)).collect(),
+            piso: desc.floor.build(&paths),
+            lights: desc.make_lights(),
+            background_color: desc.background_color(),
+            entorno: desc.entorno.clone(),
+            tonemap: desc.tonemap.build(),
+            post: desc.post.build(),
+            textures,
         }
     }
 
-    pub fn render(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, 
-                 light: &Light, cam: &Camera3D, show_normals: bool, show_wireframe: bool) {
-        // Renderizar piso
-        self.piso.render(d3);
-        // Renderizar luz
-        light.render(d3);
-        // Renderizar cubo con textura e iluminación realista
-        self.cubo.render_textured(d3, &self.texture, light, cam.position);
-        // Renderizar sombras (mejorado para evitar parpadeo)
-        self.cubo.render_shadow_stable(d3, light, self.piso.y_position);
-        // Renderizar wireframe solo si está habilitado
-        if show_wireframe {
-            self.cubo.render_wireframe(d3, cam);
+    pub fn render(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, cam: &Camera3D,
+                  options: RenderOptions) {
+        let RenderOptions { mode, shader, vertex_shader, show_normals, show_wireframe } = options;
+        match mode {
+            // Con shader (Phong o PBR): las sombras salen del shadow map
+            RenderMode::Shader | RenderMode::Pbr => {
+                self.piso.render_shaded(d3, &self.textures, shader, cam.position);
+                self.render_lights(d3);
+                for objeto in &self.objetos {
+                    objeto.render_shaded(d3, &self.textures, shader);
+                }
+            }
+            // Fallback por CPU con sombra proyectada sobre el piso
+            RenderMode::CpuPerFace => {
+                vertex_shader.begin();
+                self.piso.render(d3, &self.textures, &self.lights, cam.position, CPU_FLOOR_RESOLUTION);
+                for objeto in &self.objetos {
+                    objeto.render_textured(d3, &self.textures, &self.lights, cam.position);
+                }
+                vertex_shader.end();
+                self.render_lights(d3);
+                // Renderizar sombras (mejorado para evitar parpadeo)
+                self.render_projected_shadows(d3);
+            }
+            // Sombras trazadas en el piso con la proyectada superpuesta para validarla
+            RenderMode::RayTraced => {
+                vertex_shader.begin();
+                let triangles = self.shadow_triangles();
+                let resolution = ((RAYTRACED_FLOOR_BUDGET / triangles.len().max(1)) as f32).sqrt() as usize;
+                let resolution = resolution.clamp(RAYTRACED_FLOOR_MIN_RESOLUTION, RAYTRACED_FLOOR_RESOLUTION);
+                self.piso.render_raytraced(d3, &self.textures, &self.lights, &triangles, cam.position, resolution);
+                for objeto in &self.objetos {
+                    objeto.render_textured(d3, &self.textures, &self.lights, cam.position);
+                }
+                vertex_shader.end();
+                self.render_lights(d3);
+                self.render_projected_shadows(d3);
+            }
         }
+        // Renderizar normales y wireframe solo si están habilitados
+        for objeto in &self.objetos {
+            if show_normals {
+                objeto.render_normals(d3);
+            }
+            if show_wireframe {
+                objeto.render_wireframe(d3, cam);
+            }
+        }
+    }
+
+    fn render_lights(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+        for light in &self.lights {
+            light.render(d3);
+        }
+    }
+
+    // Una sombra proyectada por objeto y por luz
+    fn render_projected_shadows(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+        for light in &self.lights {
+            for objeto in &self.objetos {
+                objeto.render_shadow_stable(d3, light, self.piso.y_position);
+            }
+        }
+    }
+
+    // Geometría que proyecta sombras, para la pasada de profundidad desde la luz
+    pub fn render_depth(&self) {
+        for objeto in &self.objetos {
+            objeto.render_depth();
+        }
+    }
+
+    // Todos los triángulos de la escena para los rayos de sombra
+    pub fn shadow_triangles(&self) -> Vec<(Vector3, Vector3, Vector3)> {
+        let mut triangles: Vec<_> = self.objetos.iter().flat_map(|o| o.triangles()).collect();
+        triangles.extend(self.piso.triangles());
+        triangles
+    }
+
+    // Punto al que mira la luz al renderizar el shadow map
+    pub fn shadow_focus(&self) -> Vector3 {
+        Vector3::new(0.0, self.piso.y_position, 0.0)
     }
 
-    pub fn get_cube_center_y(&self) -> f32 {
-        self.cubo.position_offset
+    // Punto al que mira la cámara por defecto: el primer objeto, o el centro del piso
+    pub fn focus_point(&self) -> Vector3 {
+        self.objetos.first().map_or(self.shadow_focus(), |o| o.center())
     }
 }
@@ -1,18 +1,115 @@
 use raylib::prelude::*;
+use crate::{posproceso::PostProceso, tonos::Tonemap};
 
 pub type Framebuffer = RenderTexture2D;
 
-pub fn create(rl: &mut RaylibHandle, thread: &RaylibThread, width: i32, height: i32) -> Framebuffer {
-	rl.load_render_texture(thread, width as u32, height as u32)
-		.expect("No se pudo crear el framebuffer")
+const TONEMAP_FS: &str = "resources/shaders/tonemap.fs";
+
+pub const HDR_FORMAT: raylib::ffi::PixelFormat = raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R16G16B16A16;
+pub const LDR_FORMAT: raylib::ffi::PixelFormat = raylib::ffi::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8;
+// raylib no tiene un PixelFormat de profundidad: LoadRenderTexture marca sus texturas de profundidad
+// con el valor de ETC2_RGB (19) y aquí se hace igual. Es sólo informativo, la textura es DEPTH_COMPONENT
+pub const DEPTH_FORMAT: raylib::ffi::PixelFormat = raylib::ffi::PixelFormat::PIXELFORMAT_COMPRESSED_ETC2_RGB;
+
+// Dónde se ata cada textura al framebuffer (rlFramebufferAttach)
+pub const ATTACH_COLOR: raylib::ffi::rlFramebufferAttachType = raylib::ffi::rlFramebufferAttachType::RL_ATTACHMENT_COLOR_CHANNEL0;
+pub const ATTACH_DEPTH: raylib::ffi::rlFramebufferAttachType = raylib::ffi::rlFramebufferAttachType::RL_ATTACHMENT_DEPTH;
+pub const ATTACH_TEXTURE2D: raylib::ffi::rlFramebufferAttachTextureType =
+	raylib::ffi::rlFramebufferAttachTextureType::RL_ATTACHMENT_TEXTURE2D;
+
+// Render target HDR: color en coma flotante (RGBA16F) para que la luz pase de 1.0 sin recortarse,
+// y profundidad en textura para la profundidad de campo. Si el driver no admite el color
+// en coma flotante se usa uno de 8 bits
+pub fn create(width: i32, height: i32) -> Framebuffer {
+	if let Some(target) = load_target(width, height, HDR_FORMAT, true) {
+		return target;
+	}
+	eprintln!("Framebuffer HDR no disponible, se usa uno de 8 bits (los brillos se recortan)");
+	load_target(width, height, LDR_FORMAT, true).expect("No se pudo crear el framebuffer")
+}
+
+// Render texture con filtro bilineal; con `depth` lleva además una textura de profundidad
+pub fn load_target(width: i32, height: i32, format: raylib::ffi::PixelFormat, depth: bool) -> Option<Framebuffer> {
+	unsafe {
+		let mut target: raylib::ffi::RenderTexture2D = std::mem::zeroed();
+		target.id = raylib::ffi::rlLoadFramebuffer();
+		if target.id == 0 {
+			return None;
+		}
+
+		raylib::ffi::rlEnableFramebuffer(target.id);
+		target.texture.id = raylib::ffi::rlLoadTexture(std::ptr::null(), width, height, format as i32, 1);
+		target.texture.width = width;
+		target.texture.height = height;
+		target.texture.format = format as i32;
+		target.texture.mipmaps = 1;
+		raylib::ffi::rlFramebufferAttach(
+			target.id,
+			target.texture.id,
+			ATTACH_COLOR as i32,
+			ATTACH_TEXTURE2D as i32,
+			0,
+		);
+		if depth {
+			target.depth.id = raylib::ffi::rlLoadTextureDepth(width, height, false);
+			target.depth.width = width;
+			target.depth.height = height;
+			target.depth.format = DEPTH_FORMAT as i32;
+			target.depth.mipmaps = 1;
+			raylib::ffi::rlFramebufferAttach(
+				target.id,
+				target.depth.id,
+				ATTACH_DEPTH as i32,
+				ATTACH_TEXTURE2D as i32,
+				0,
+			);
+		}
+		let complete = raylib::ffi::rlFramebufferComplete(target.id);
+		raylib::ffi::rlDisableFramebuffer();
+
+		if !complete {
+			raylib::ffi::UnloadRenderTexture(target);
+			return None;
+		}
+		// Las pasadas de post-proceso leen entre píxeles (bloom a media resolución, FXAA)
+		raylib::ffi::SetTextureFilter(target.texture, raylib::ffi::TextureFilter::TEXTURE_FILTER_BILINEAR as i32);
+		Some(RenderTexture2D::from_raw(target))
+	}
+}
+
+// Exposición y operador de tono: la pasada que lleva el HDR a sRGB dentro de la cadena de post-proceso
+pub struct TonemapPass {
+	shader: Shader,
+	exposure_loc: i32,
+	tonemapper_loc: i32,
+	pub settings: Tonemap,
+}
+
+impl TonemapPass {
+	pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, settings: Tonemap) -> Self {
+		let shader = rl.load_shader(thread, None, Some(TONEMAP_FS));
+		Self {
+			exposure_loc: shader.get_shader_location("exposure"),
+			tonemapper_loc: shader.get_shader_location("tonemapper"),
+			shader,
+			settings,
+		}
+	}
+
+	// Sube `settings` a los uniforms y devuelve el shader listo para la pasada
+	pub fn prepare(&mut self) -> &Shader {
+		let exposure = self.settings.exposure_scale();
+		let tonemapper = self.settings.tonemapper.shader_id();
+		self.shader.set_shader_value(self.exposure_loc, exposure);
+		self.shader.set_shader_value(self.tonemapper_loc, tonemapper);
+		&self.shader
+	}
 }
 
-// Dibuja el contenido del framebuffer a pantalla corrigiendo el flip vertical del render texture
-pub fn blit_to_screen(d: &mut RaylibDrawHandle, fb: &Framebuffer) {
-	let tex = fb.texture();
-	let src = Rectangle::new(0.0, 0.0, tex.width() as f32, -(tex.height() as f32));
-	let pos = Vector2::new(0.0, 0.0);
-	d.draw_texture_rec(tex, src, pos, Color::WHITE);
+// Lleva el framebuffer a pantalla a través de la cadena de post-proceso (con el tonemap en medio);
+// cada pasada corrige el flip vertical del render texture
+pub fn blit_to_screen(d: &mut RaylibDrawHandle, fb: &Framebuffer, tonemap: &mut TonemapPass, post: &mut PostProceso) {
+	post.render(d, fb, tonemap);
 }
 
 // Módulo para framebuffer
@@ -0,0 +1,235 @@
+use raylib::prelude::*;
+use crate::controles::{LightControls, LightOrbit};
+use crate::entrada::{Action, InputMap};
+use crate::intersecto_ray::{self, Ray};
+use crate::trazador;
+
+// Tamaño del gizmo respecto de su distancia a la cámara: se ve igual de grande desde cualquier lado
+const GIZMO_SCALE: f32 = 0.15;
+// Tolerancia para tomar un eje, en la misma escala que el gizmo
+const PICK_TOLERANCE: f32 = 0.08;
+// Los cuadrados de los planos van de PLANE_START a PLANE_END a lo largo de cada eje
+const PLANE_START: f32 = 0.25;
+const PLANE_END: f32 = 0.45;
+// Radio con el que se toma la esfera de una luz (el halo de Light::render)
+const LIGHT_PICK_RADIUS: f32 = 0.25;
+
+const AXES: [Vector3; 3] = [
+    Vector3 { x: 1.0, y: 0.0, z: 0.0 },
+    Vector3 { x: 0.0, y: 1.0, z: 0.0 },
+    Vector3 { x: 0.0, y: 0.0, z: 1.0 },
+];
+const AXIS_COLORS: [Color; 3] = [Color::RED, Color::GREEN, Color::BLUE];
+const RADIUS_COLOR: Color = Color::MAGENTA;
+const ACTIVE_COLOR: Color = Color::YELLOW;
+
+// Parte del gizmo que se arrastra
+#[derive(Clone, Copy, PartialEq)]
+enum Handle {
+    Axis(usize),  // A lo largo de X, Y (altura) o Z
+    Plane(usize), // Sobre el plano perpendicular a ese eje
+    Radius,       // Alejándose o acercándose al eje Y: cambia el radio de la órbita
+    Sphere,       // La esfera de la luz: en el plano de la pantalla
+}
+
+struct Drag {
+    handle: Handle,
+    light: usize, // Índice en LightControls::orbits (TAB no cambia la luz que se arrastra)
+    start_position: Vector3,
+    start_hit: Vector3,  // Donde el mouse tocó el plano de arrastre al empezar
+    plane_normal: Vector3,
+    axis: Option<Vector3>, // Con un eje, sólo cuenta el movimiento a lo largo de él
+}
+
+// Gizmo de traslación de la luz seleccionada: click sobre una luz para elegirla y arrastrarla,
+// o sobre sus ejes y planos para moverla sólo en esas direcciones
+#[derive(Default)]
+pub struct LightGizmo {
+    pub visible: bool,
+    hovered: Option<Handle>,
+    drag: Option<Drag>,
+}
+
+impl LightGizmo {
+    // Devuelve true mientras el gizmo usa el mouse (la cámara no debe girar con el mismo arrastre)
+    pub fn update(&mut self, rl: &RaylibHandle, input: &InputMap, cam: &Camera3D, lights: &mut LightControls) -> bool {
+        if input.is_pressed(rl, Action::ToggleGizmo) {
+            self.visible = !self.visible;
+        }
+        let mouse = rl.get_mouse_position();
+        let ray = trazador::camera_ray(cam, mouse.x, mouse.y, rl.get_screen_width() as u32, rl.get_screen_height() as u32);
+
+        if let Some(drag) = &self.drag {
+            if !input.is_down(rl, Action::Grab) {
+                self.drag = None;
+                return false;
+            }
+            if let (Some(orbit), Some(hit)) = (lights.orbits.get_mut(drag.light), intersecto_ray::ray_plane_intersect(&ray, drag.start_position, drag.plane_normal)) {
+                orbit.set_position(drag.target(hit));
+            }
+            return true;
+        }
+
+        self.hovered = if self.visible {
+            lights.selected().and_then(|orbit| pick_handle(&ray, orbit, cam))
+        } else {
+            None
+        };
+        if !input.is_pressed(rl, Action::Grab) {
+            return false;
+        }
+
+        // Click sobre otra luz: pasa a ser la seleccionada y se arrastra por su esfera
+        let handle = match self.hovered {
+            Some(handle) => handle,
+            None => match pick_light(&ray, lights) {
+                Some(index) => {
+                    lights.selected = index;
+                    self.visible = true;
+                    Handle::Sphere
+                }
+                None => return false,
+            },
+        };
+        let light = lights.selected;
+        let Some(orbit) = lights.orbits.get_mut(light) else { return false };
+        self.drag = Drag::start(handle, light, &ray, orbit, cam);
+        if self.drag.is_some() {
+            // Movida a mano la luz se queda donde se suelta
+            orbit.auto_rotate = false;
+        }
+        self.drag.is_some()
+    }
+
+    pub fn render(&self, d: &mut RaylibDrawHandle, cam: Camera3D, lights: &LightControls) {
+        let Some(orbit) = lights.selected().filter(|_| self.visible) else { return };
+        let active = self.drag.as_ref().map(|drag| drag.handle).or(self.hovered);
+        let color = |handle: Handle, base: Color| if active == Some(handle) { ACTIVE_COLOR } else { base };
+        let pos = orbit.position();
+        let size = gizmo_size(pos, &cam);
+
+        // Se dibuja sobre la imagen ya terminada: siempre visible y fuera de las capturas
+        let mut d3 = d.begin_mode3D(cam);
+
+        // Órbita actual (radio y altura)
+        d3.draw_circle_3D(Vector3::new(0.0, orbit.height, 0.0), orbit.radius, Vector3::new(1.0, 0.0, 0.0), 90.0,
+                          Color::new(200, 200, 200, 120));
+
+        for (i, axis) in AXES.iter().enumerate() {
+            let c = color(Handle::Axis(i), AXIS_COLORS[i]);
+            let tip = pos + *axis * size;
+            d3.draw_line_3D(pos, tip, c);
+            d3.draw_cube(tip, size * 0.06, size * 0.06, size * 0.06, c);
+
+            let c = color(Handle::Plane(i), AXIS_COLORS[i]);
+            let corners = plane_corners(pos, i, size);
+            for (k, corner) in corners.iter().enumerate() {
+                d3.draw_line_3D(*corner, corners[(k + 1) % 4], c);
+            }
+        }
+
+        let c = color(Handle::Radius, RADIUS_COLOR);
+        let tip = pos + radial(orbit) * (size * 0.8);
+        d3.draw_line_3D(pos, tip, c);
+        d3.draw_sphere(tip, size * 0.04, c);
+    }
+}
+
+impl Drag {
+    fn start(handle: Handle, light: usize, ray: &Ray, orbit: &LightOrbit, cam: &Camera3D) -> Option<Self> {
+        let axis = match handle {
+            Handle::Axis(i) => Some(AXES[i]),
+            Handle::Radius => Some(radial(orbit)),
+            Handle::Plane(_) | Handle::Sphere => None,
+        };
+        let plane_normal = match (handle, axis) {
+            // Plano que contiene al eje y mira lo más posible a la cámara
+            (_, Some(axis)) => {
+                let normal = axis.cross(ray.direction.cross(axis));
+                if normal.length() < 1e-3 {
+                    return None; // Eje apuntando a la cámara: no hay hacia dónde arrastrar
+                }
+                normal.normalized()
+            }
+            (Handle::Plane(i), None) => AXES[i],
+            _ => (cam.target - cam.position).normalized(),
+        };
+        let start_position = orbit.position();
+        let start_hit = intersecto_ray::ray_plane_intersect(ray, start_position, plane_normal)?;
+        Some(Self { handle, light, start_position, start_hit, plane_normal, axis })
+    }
+
+    // Posición de la luz con el mouse tocando el plano de arrastre en `hit`
+    fn target(&self, hit: Vector3) -> Vector3 {
+        let delta = hit - self.start_hit;
+        match (self.handle, self.axis) {
+            (Handle::Radius, Some(axis)) => {
+                // El radio no baja de cero: la luz se detiene sobre el eje Y
+                let radius = Vector3::new(self.start_position.x, 0.0, self.start_position.z).length();
+                self.start_position + axis * delta.dot(axis).max(-radius)
+            }
+            (_, Some(axis)) => self.start_position + axis * delta.dot(axis),
+            (_, None) => self.start_position + delta,
+        }
+    }
+}
+
+fn gizmo_size(pos: Vector3, cam: &Camera3D) -> f32 {
+    (pos - cam.position).length() * GIZMO_SCALE
+}
+
+// Dirección horizontal desde el eje Y hacia la luz
+fn radial(orbit: &LightOrbit) -> Vector3 {
+    Vector3::new(orbit.rotation.cos(), 0.0, orbit.rotation.sin())
+}
+
+// Cuadrado del plano perpendicular al eje `i`, en la esquina entre los otros dos ejes
+fn plane_corners(pos: Vector3, i: usize, size: f32) -> [Vector3; 4] {
+    let (u, v) = (AXES[(i + 1) % 3], AXES[(i + 2) % 3]);
+    let (a, b) = (PLANE_START * size, PLANE_END * size);
+    [pos + u * a + v * a, pos + u * b + v * a, pos + u * b + v * b, pos + u * a + v * b]
+}
+
+// Parte del gizmo bajo el mouse: primero los planos, después el eje o el radio más cercano
+fn pick_handle(ray: &Ray, orbit: &LightOrbit, cam: &Camera3D) -> Option<Handle> {
+    let pos = orbit.position();
+    let size = gizmo_size(pos, cam);
+
+    let plane = (0..3).find(|&i| {
+        let (u, v) = (AXES[(i + 1) % 3], AXES[(i + 2) % 3]);
+        intersecto_ray::ray_plane_intersect(ray, pos, AXES[i]).is_some_and(|hit| {
+            let local = hit - pos;
+            let range = PLANE_START * size..=PLANE_END * size;
+            range.contains(&local.dot(u)) && range.contains(&local.dot(v))
+        })
+    });
+    if let Some(i) = plane {
+        return Some(Handle::Plane(i));
+    }
+
+    let segments = [
+        (Handle::Axis(0), pos + AXES[0] * size),
+        (Handle::Axis(1), pos + AXES[1] * size),
+        (Handle::Axis(2), pos + AXES[2] * size),
+        (Handle::Radius, pos + radial(orbit) * (size * 0.8)),
+    ];
+    let closest = segments.iter()
+        .map(|&(handle, tip)| (handle, intersecto_ray::ray_segment_distance(ray, pos, tip)))
+        .filter(|&(_, distance)| distance < PICK_TOLERANCE * size)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((handle, _)) = closest {
+        return Some(handle);
+    }
+
+    intersecto_ray::ray_sphere_intersect(ray, pos, LIGHT_PICK_RADIUS).map(|_| Handle::Sphere)
+}
+
+// Luz más cercana cuya esfera toca el rayo
+fn pick_light(ray: &Ray, lights: &LightControls) -> Option<usize> {
+    lights.orbits.iter().enumerate()
+        .filter_map(|(i, orbit)| intersecto_ray::ray_sphere_intersect(ray, orbit.position(), LIGHT_PICK_RADIUS).map(|t| (i, t)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+// Módulo para el gizmo de traslación de las luces
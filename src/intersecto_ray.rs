@@ -13,6 +13,11 @@ impl Ray {
 
 // Test de intersección rayo-triángulo (Möller-Trumbore)
 pub fn ray_triangle_intersect(ray: &Ray, v0: Vector3, v1: Vector3, v2: Vector3) -> Option<f32> {
+    ray_triangle_intersect_uv(ray, v0, v1, v2).map(|(t, _, _)| t)
+}
+
+// Igual que ray_triangle_intersect pero devuelve también las coordenadas baricéntricas (t, u, v)
+pub fn ray_triangle_intersect_uv(ray: &Ray, v0: Vector3, v1: Vector3, v2: Vector3) -> Option<(f32, f32, f32)> {
     let edge1 = v1 - v0;
     let edge2 = v2 - v0;
     let h = ray.direction.cross(edge2);
@@ -40,7 +45,7 @@ pub fn ray_triangle_intersect(ray: &Ray, v0: Vector3, v1: Vector3, v2: Vector3)
     let t = f * edge2.dot(q);
     
     if t > f32::EPSILON {
-        Some(t)
+        Some((t, u, v))
     } else {
         None
     }
@@ -61,3 +66,38 @@ pub fn is_in_shadow(point: Vector3, light_pos: Vector3, triangles: &[(Vector3, V
     }
     false
 }
+
+// Distancia a lo largo del rayo hasta la esfera (la primera cara que toca)
+pub fn ray_sphere_intersect(ray: &Ray, center: Vector3, radius: f32) -> Option<f32> {
+    let oc = ray.origin - center;
+    let b = oc.dot(ray.direction);
+    let c = oc.dot(oc) - radius * radius;
+    let disc = b * b - c;
+    if disc < 0.0 {
+        return None;
+    }
+    let t = -b - disc.sqrt();
+    if t >= 0.0 { Some(t) } else if c <= 0.0 { Some(0.0) } else { None } // c <= 0: el origen está adentro
+}
+
+// Punto donde el rayo cruza el plano que pasa por `point` con normal `normal`
+pub fn ray_plane_intersect(ray: &Ray, point: Vector3, normal: Vector3) -> Option<Vector3> {
+    let denom = ray.direction.dot(normal);
+    if denom.abs() < 1e-5 {
+        return None; // Rayo paralelo al plano
+    }
+    let t = (point - ray.origin).dot(normal) / denom;
+    if t < 0.0 { None } else { Some(ray.origin + ray.direction * t) }
+}
+
+// Distancia mínima entre el rayo y el segmento a-b
+pub fn ray_segment_distance(ray: &Ray, a: Vector3, b: Vector3) -> f32 {
+    let v = b - a;
+    let w = ray.origin - a;
+    let (bb, cc, dd, ee) = (ray.direction.dot(v), v.dot(v), ray.direction.dot(w), v.dot(w));
+    let denom = cc - bb * bb; // |dirección| = 1
+    let t = if denom.abs() < 1e-6 { 0.0 } else { ((ee - bb * dd) / denom).clamp(0.0, 1.0) };
+    let closest = a + v * t;
+    let s = (closest - ray.origin).dot(ray.direction).max(0.0);
+    (ray.origin + ray.direction * s - closest).length()
+}
@@ -1,28 +1,139 @@
 use raylib::prelude::*;
+use crate::material::Material;
 
+// Máximo de luces que recibe el shader (ver MAX_LIGHTS en lighting.fs)
+pub const MAX_LIGHTS: usize = 4;
+
+// Distancia a la que se coloca el "origen" de una luz direccional para los rayos de sombra
+const DIRECTIONAL_DISTANCE: f32 = 1000.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+	Point,
+	Directional,
+	Spot,
+}
+
+impl LightKind {
+	// Mismo valor que LIGHT_POINT / LIGHT_DIRECTIONAL / LIGHT_SPOT en lighting.fs
+	pub fn shader_id(self) -> i32 {
+		match self {
+			LightKind::Point => 0,
+			LightKind::Directional => 1,
+			LightKind::Spot => 2,
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			LightKind::Point => "Puntual",
+			LightKind::Directional => "Direccional",
+			LightKind::Spot => "Foco",
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub struct Light {
+	pub kind: LightKind,
 	pub pos: [f32; 3],
+	pub direction: [f32; 3],   // Hacia dónde apunta (direccional y foco)
 	pub color: [f32; 3],
+	pub attenuation: [f32; 3], // Constante, lineal y cuadrática (puntual y foco)
+	pub inner_angle: f32,      // Semiángulo del cono en grados (foco)
+	pub outer_angle: f32,
 }
 
 impl Light {
 	pub fn new(pos: [f32; 3], color: [f32; 3]) -> Self {
-		Self { pos, color }
+		Self {
+			kind: LightKind::Point,
+			pos,
+			direction: [0.0, -1.0, 0.0],
+			color,
+			attenuation: [1.0, 0.05, 0.0],
+			inner_angle: 20.0,
+			outer_angle: 30.0,
+		}
 	}
-	
+
 	pub fn position_vec3(&self) -> Vector3 {
 		Vector3::new(self.pos[0], self.pos[1], self.pos[2])
 	}
 
+	pub fn direction_vec3(&self) -> Vector3 {
+		Vector3::new(self.direction[0], self.direction[1], self.direction[2]).normalized()
+	}
+
+	// Dirección desde `point` hacia la luz, distancia y factor de atenuación (incluye el cono del foco)
+	pub fn incidence(&self, point: Vector3) -> (Vector3, f32, f32) {
+		if self.kind == LightKind::Directional {
+			return (-self.direction_vec3(), f32::INFINITY, 1.0);
+		}
+
+		let to_light = self.position_vec3() - point;
+		let distance = to_light.length();
+		let light_dir = to_light / distance;
+		let [c, l, q] = self.attenuation;
+		let mut factor = 1.0 / (c + l * distance + q * distance * distance);
+
+		if self.kind == LightKind::Spot {
+			let cos_theta = (-light_dir).dot(self.direction_vec3());
+			factor *= smoothstep(self.outer_angle.to_radians().cos(), self.inner_angle.to_radians().cos(), cos_theta);
+		}
+		(light_dir, distance, factor)
+	}
+
+	// Punto hacia el que se lanzan los rayos de sombra desde `point`
+	pub fn shadow_target(&self, point: Vector3) -> Vector3 {
+		match self.kind {
+			LightKind::Directional => point - self.direction_vec3() * DIRECTIONAL_DISTANCE,
+			_ => self.position_vec3(),
+		}
+	}
+
+	// Proyecta `v` sobre el plano y = plane_y siguiendo el rayo de la luz
+	pub fn project_to_plane(&self, v: Vector3, plane_y: f32) -> Vector3 {
+		match self.kind {
+			LightKind::Directional => {
+				let dir = self.direction_vec3();
+				let t = (v.y - plane_y) / -dir.y;
+				Vector3::new(v.x + dir.x * t, plane_y, v.z + dir.z * t)
+			}
+			_ => {
+				let light_pos = self.position_vec3();
+				let t = (v.y - plane_y) / (light_pos.y - plane_y);
+				Vector3::new(
+					v.x - t * (light_pos.x - v.x),
+					plane_y,
+					v.z - t * (light_pos.z - v.z)
+				)
+			}
+		}
+	}
+
+	// La luz está por encima del plano (o apunta hacia abajo si es direccional)
+	pub fn lights_plane_from_above(&self, plane_y: f32) -> bool {
+		match self.kind {
+			LightKind::Directional => self.direction_vec3().y < -0.1,
+			_ => self.pos[1] > plane_y + 0.1, // Margen para evitar divisiones por cero
+		}
+	}
+
 	pub fn render(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
 		let light_pos = self.position_vec3();
-		
+
 		// Esfera principal de la luz
 		d3.draw_sphere(light_pos, 0.15, Color::new(255, 255, 100, 255));
 		d3.draw_sphere_wires(light_pos, 0.18, 8, 8, Color::WHITE);
-		
+
 		// Halo de luz
 		d3.draw_sphere_wires(light_pos, 0.25, 12, 12, Color::new(255, 255, 150, 150));
+
+		// Dirección de las luces direccionales y focos
+		if self.kind != LightKind::Point {
+			d3.draw_line_3D(light_pos, light_pos + self.direction_vec3() * 1.0, Color::new(255, 255, 150, 255));
+		}
 	}
 }
 
@@ -30,40 +141,95 @@ pub fn color_vec(l: &Light) -> Vector3 {
 	Vector3::new(l.color[0], l.color[1], l.color[2])
 }
 
-// Ray-traced shading con sombras mejoradas y mejor contraste
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * (3.0 - 2.0 * t)
+}
+
+// Luz directa de una luz con rayo de sombra (sin ambiente), ya modulada por el albedo
+fn direct_with_shadow(
+	n: Vector3,
+	point: Vector3,
+	light: &Light,
+	material: &Material,
+	shadow_triangles: &[(Vector3, Vector3, Vector3)]
+) -> Vector3 {
+	let (light_dir, _, factor) = light.incidence(point);
+	let ndotl = n.dot(light_dir).max(0.0);
+
+	// Ray tracing para sombras con mayor intensidad
+	let in_shadow = crate::intersecto_ray::is_in_shadow(point, light.shadow_target(point), shadow_triangles);
+	let shadow_factor = if in_shadow {
+		crate::colores::SHADOW_INTENSITY
+	} else {
+		1.0
+	};
+
+	// Calcular difuso con mejor contraste - usar función cuadrática para más drama
+	let diffuse = (ndotl * ndotl * factor * shadow_factor * material.diffuse).min(1.0);
+	color_vec(light) * material.albedo * diffuse
+}
+
+// Ray-traced shading con sombras mejoradas y mejor contraste; suma todas las luces
+// y cuenta la luz ambiente una sola vez (con el color de la primera luz)
 pub fn shade_with_shadows(
-	normal: Vector3, 
+	normal: Vector3,
 	point: Vector3,
-	light: &Light, 
-	base: Color, 
-	light_col: Vector3, 
-	ambient: f32,
+	lights: &[Light],
+	material: &Material,
 	shadow_triangles: &[(Vector3, Vector3, Vector3)]
 ) -> Color {
 	let n = normal.normalized();
-	let light_pos = light.position_vec3();
-	let light_dir = (light_pos - point).normalized();
-	let ndotl = n.dot(light_dir).max(0.0);
+	let ambient_col = lights.first().map_or(Vector3::one(), color_vec);
+	let mut color = ambient_col * material.albedo * material.ambient + material.emissive;
+	for light in lights {
+		color = color + direct_with_shadow(n, point, light, material, shadow_triangles);
+	}
+
+	// Sin recortar: el color de vértice lleva la luz comprimida (ver tonos::encode_vertex_color)
+	crate::tonos::encode_vertex_color(color)
+}
+// Iluminación por cara en RGB: suma todas las luces con su color y las modula con el material
+pub fn calculate_realistic_lighting(normal: Vector3, point: Vector3, lights: &[Light], camera_pos: Vector3, material: &Material) -> Color {
+	let n = normal.normalized();
+	let view_dir = (camera_pos - point).normalized();
 	
-	// Ray tracing para sombras con mayor intensidad
-	let in_shadow = crate::intersecto_ray::is_in_shadow(point, light_pos, shadow_triangles);
-	let shadow_factor = if in_shadow { 
-		crate::colores::SHADOW_INTENSITY 
-	} else { 
-		1.0 
-	};
+	let mut diffuse = Vector3::zero();
+	let mut specular = Vector3::zero();
+	for light in lights {
+		// Dirección hacia la luz y atenuación según el tipo de luz
+		let (light_dir, _, attenuation) = light.incidence(point);
+		let light_col = color_vec(light);
+		
+		// Iluminación difusa con contraste extremo
+		let dot_product = n.dot(light_dir);
+		
+		// Para evitar que las caras desaparezcan, usar el valor absoluto del dot product
+		// Esto hace que ambos lados de la cara reciban algo de iluminación
+		let diffuse_raw = dot_product.abs().max(0.1); // Mínimo 0.1 para visibilidad
+		
+		// Sin luz ambiente prácticamente, solo luz directa
+		diffuse = diffuse + light_col * (diffuse_raw * diffuse_raw * attenuation * material.diffuse);
+		
+		// Iluminación especular muy brillante
+		let reflect_dir = reflect_vector(-light_dir, n);
+		let spec_factor = view_dir.dot(reflect_dir).max(0.0);
+		specular = specular + light_col * (spec_factor.powf(material.shininess) * attenuation * material.specular);
+	}
 	
-	// Calcular difuso con mejor contraste - usar función cuadrática para más drama
-	let diffuse = (ndotl * ndotl * shadow_factor * crate::colores::DIFFUSE_STRENGTH).min(1.0);
+	// Luz ambiente del material, con el color de la primera luz
+	let ambient_col = lights.first().map_or(Vector3::one(), color_vec);
+	let ambient = ambient_col * material.ambient;
 	
-	// Usar la luz ambiente baja de las constantes
-	let final_intensity = crate::colores::AMBIENT_LIGHT + diffuse;
+	// El albedo sólo tiñe ambiente y difuso; el especular conserva el color de la luz
+	let color = (ambient + diffuse) * material.albedo + specular + material.emissive;
 	
-	Color::new(
-		(base.r as f32 * final_intensity * light_col.x).clamp(0.0, 255.0) as u8,
-		(base.g as f32 * final_intensity * light_col.y).clamp(0.0, 255.0) as u8,
-		(base.b as f32 * final_intensity * light_col.z).clamp(0.0, 255.0) as u8,
-		255,
-	)
+	crate::tonos::encode_vertex_color(color)
 }
+
+// Función auxiliar para calcular reflexión
+fn reflect_vector(incident: Vector3, normal: Vector3) -> Vector3 {
+	incident - normal * (2.0 * incident.dot(normal))
+}
+
 // Módulo para luces
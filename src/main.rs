@@ -10,12 +10,111 @@ mod piso;
 mod controles;
 mod ui;
 mod escena;
+mod trazador;
+mod shaders;
+mod sombras;
+mod archivo_escena;
+mod objeto;
+mod material;
+mod pbr;
+mod malla;
+mod archivo_malla;
+mod primitivas;
+mod relieve;
+mod entorno;
+mod tonos;
+mod posproceso;
+mod captura;
+mod transform;
+mod entrada;
+mod gizmo;
 
-use controles::{CameraControls, LightControls};
-use escena::Escena;
-use light::Light;
+use archivo_escena::SceneFile;
+use controles::CameraControls;
+use entrada::{Action, InputMap};
+use escena::{Escena, RenderMode, RenderOptions};
+
+// Patrón de la secuencia que se graba con G (ver captura::Secuencia)
+const SEQUENCE_PATTERN: &str = "capturas/secuencia.png";
+// Acción de cada efecto de post-proceso, en el orden de posproceso::Efecto::ALL
+const EFFECT_ACTIONS: [Action; 6] = [
+    Action::ToggleBloom, Action::ToggleDepthOfField, Action::ToggleFxaa,
+    Action::ToggleColorGrading, Action::ToggleChromaticAberration, Action::ToggleVignette,
+];
+// Tiempo que avanza cada fotograma de una secuencia (60 fps)
+const SEQUENCE_FRAME_TIME: f32 = 1.0 / 60.0;
+// Tope del tiempo de un fotograma: tras un tirón la cámara no salta de golpe
+const MAX_FRAME_TIME: f32 = 0.1;
+
+// Argumentos (ver USAGE)
+const USAGE: &str = "Uso: cuborenderizado [escena.toml] [--render salida.png] [--spp 16] \
+[--sequence capturas/orbita.png] [--frames 360] [--controls controles.toml]";
+
+struct Args {
+    scene: Option<String>,
+    render: Option<String>,
+    spp: Option<u32>,
+    sequence: Option<String>, // Graba desde el primer fotograma
+    frames: Option<u32>, // Con --sequence, cierra la ventana después de tantos fotogramas
+    controls: Option<String>, // Archivo de controles (por defecto controles.toml si existe)
+}
+
+fn parse_args() -> Args {
+    let mut args = Args { scene: None, render: None, spp: None, sequence: None, frames: None, controls: None };
+    let mut it = std::env::args().skip(1).peekable();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--render" => args.render = Some(it.next().unwrap_or_else(|| "render.png".to_string())),
+            "--spp" => args.spp = it.next().and_then(|v| v.parse().ok()),
+            // El patrón es opcional: "--sequence --frames 120" no lo toma de la opción siguiente
+            "--sequence" => args.sequence = Some(it.next_if(|v| !v.starts_with("--"))
+                .unwrap_or_else(|| SEQUENCE_PATTERN.to_string())),
+            "--frames" => args.frames = it.next().and_then(|v| v.parse().ok()),
+            "--controls" => args.controls = it.next(),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            // Una opción mal escrita no se toma como la ruta de la escena
+            _ if arg.starts_with('-') => {
+                eprintln!("Argumento desconocido: {}\n{}", arg, USAGE);
+                std::process::exit(1);
+            }
+            _ => args.scene = Some(arg),
+        }
+    }
+    args
+}
 
 fn main() {
+    let args = parse_args();
+
+    // Escena desde archivo o la escena por defecto
+    let desc = match &args.scene {
+        Some(path) => SceneFile::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => SceneFile::default(),
+    };
+
+    // Modo sin ventana
+    if let Some(output) = &args.render {
+        render_headless(&desc, output, args.spp);
+        return;
+    }
+
+    // Controles por defecto, o con los del archivo encima
+    let controls_path = args.controls.as_deref()
+        .or(std::path::Path::new(entrada::CONTROLS_PATH).exists().then_some(entrada::CONTROLS_PATH));
+    let input = match controls_path {
+        Some(path) => InputMap::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => InputMap::default(),
+    };
+
     // Inicialización de la ventana
     let (mut rl, thread) = raylib::init()
         .size(800, 600)
@@ -24,74 +123,236 @@ fn main() {
 
     rl.set_target_fps(60);
 
-    // Cargar textura para el cubo
-    let texture = rl.load_texture(&thread, "assets/texture1.png").expect("No se pudo cargar la textura");
+    // Cargar texturas de los objetos
+    let textures = desc.texture_paths().iter()
+        .map(|path| archivo_malla::load_texture(&mut rl, &thread, path).expect("No se pudo cargar la textura"))
+        .collect();
 
-    // Inicialización de la escena con textura
-    let escena = Escena::new_with_texture(texture);
+    // Inicialización de la escena con texturas
+    let mut escena = Escena::from_description(&desc, textures);
     
     // Configuración de cámara
-    let mut cam = camera::make_camera();
-    cam.target = Vector3::new(0.0, escena.get_cube_center_y(), 0.0);
-    
-    // Configuración de luz
-    let mut light = Light::new([4.0, 6.0, 2.0], [1.0, 1.0, 1.0]);
+    let mut cam = desc.make_camera(escena.focus_point());
     
     // Configuración de controles
     let mut camera_controls = CameraControls::new(cam.position, cam.target);
-    let mut light_controls = LightControls::new();
+    camera_controls.set_floor(escena.piso.y_position, escena.piso.size);
+    let mut light_controls = desc.make_light_controls(&escena.lights);
+    let mut light_gizmo = gizmo::LightGizmo::default();
     
-    // Framebuffer
+    // Shaders de iluminación por fragmento (Phong y PBR)
+    let mut lighting_shader = shaders::LightingShader::load(&mut rl, &thread);
+    let mut pbr_shader = shaders::LightingShader::load_pbr(&mut rl, &thread);
+    let mut render_mode = if lighting_shader.is_ready() { RenderMode::Shader } else { RenderMode::CpuPerFace };
+
+    // Mapa de entorno: fondo y, con `ibl`, luz ambiente y reflejos en los dos shaders
+    let mut skybox = escena.entorno.as_ref().map(|e| entorno::Skybox::load(&mut rl, &thread, e));
+    lighting_shader.set_environment(skybox.as_ref());
+    pbr_shader.set_environment(skybox.as_ref());
+
+    // Un shadow map por luz (resolución y radio PCF de la tabla [shadows])
+    let mut shadow_maps = sombras::ShadowMaps::new(desc.shadows.build(), escena.lights.len());
+
+    // Framebuffer HDR y pasada de tonemap hacia la pantalla
     let fb_size = (800, 600);
-    let mut fb = framebuffer::create(&mut rl, &thread, fb_size.0, fb_size.1);
+    let mut fb = framebuffer::create(fb_size.0, fb_size.1);
+    let mut tonemap = framebuffer::TonemapPass::load(&mut rl, &thread, escena.tonemap);
+    let mut post = posproceso::PostProceso::load(&mut rl, &thread, escena.post.clone(), fb_size.0, fb_size.1);
+    // Los modos por CPU dibujan con este shader para no recortar la luz en el color de vértice
+    let vertex_shader = shaders::VertexColorShader::load(&mut rl, &thread);
     
     // Estado de visualización
     let mut show_normals = false;
     let mut show_wireframe = true; // Nuevo control para mostrar/ocultar bordes
     let mut show_ui = false; // Control para mostrar/ocultar el menú UI
 
+    // Capturas: una pendiente para este fotograma y la secuencia en curso
+    let mut screenshot: Option<std::path::PathBuf> = None;
+    let mut secuencia = args.sequence.as_deref().map(|pattern| captura::Secuencia::new(pattern, args.frames));
+
     // Loop principal
     while !rl.window_should_close() {
         // Manejo de entrada
-        if rl.is_key_pressed(KeyboardKey::KEY_N) { 
+        if input.is_pressed(&rl, Action::ToggleNormals) { 
             show_normals = !show_normals; 
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_B) { 
+        if input.is_pressed(&rl, Action::ToggleWireframe) { 
             show_wireframe = !show_wireframe; 
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_U) { 
+        if input.is_pressed(&rl, Action::ToggleUi) { 
             show_ui = !show_ui; // Presiona 'U' para mostrar/ocultar el menú
         }
+        if input.is_pressed(&rl, Action::CycleRenderMode) {
+            render_mode = render_mode.next();
+            // Se saltan los modos cuyo shader no compiló
+            while !mode_available(render_mode, &lighting_shader, &pbr_shader) {
+                render_mode = render_mode.next();
+            }
+        }
+        // Con el modificador cambian la resolución del shadow map (se vuelve a crear en la próxima pasada)
+        let shadows = &mut shadow_maps.settings;
+        if input.is_pressed(&rl, Action::PcfUp) {
+            if input.is_down(&rl, Action::Modifier) {
+                shadows.resolution = (shadows.resolution * 2).min(sombras::MAX_RESOLUTION);
+            } else {
+                shadows.pcf_radius = (shadows.pcf_radius + 1).min(sombras::MAX_PCF_RADIUS);
+            }
+        }
+        if input.is_pressed(&rl, Action::PcfDown) {
+            if input.is_down(&rl, Action::Modifier) {
+                shadows.resolution = (shadows.resolution / 2).max(sombras::MIN_RESOLUTION);
+            } else {
+                shadows.pcf_radius = (shadows.pcf_radius - 1).max(0);
+            }
+        }
+        if input.is_pressed(&rl, Action::CycleTonemapper) {
+            tonemap.settings.tonemapper = tonemap.settings.tonemapper.next();
+        }
+        if input.is_pressed(&rl, Action::ExposureUp) {
+            tonemap.settings.exposure = (tonemap.settings.exposure + 0.5).min(8.0);
+        }
+        if input.is_pressed(&rl, Action::ExposureDown) {
+            tonemap.settings.exposure = (tonemap.settings.exposure - 0.5).max(-8.0);
+        }
+        if input.is_pressed(&rl, Action::Screenshot) {
+            // Con Shift, la luz lineal del framebuffer en EXR
+            let hdr = input.is_down(&rl, Action::Modifier);
+            screenshot = Some(captura::next_screenshot_path(if hdr { "exr" } else { "png" }));
+        }
+        if input.is_pressed(&rl, Action::ToggleRecording) {
+            secuencia = match secuencia.take() {
+                Some(seq) => {
+                    println!("Secuencia detenida: {} fotogramas", seq.frame);
+                    None
+                }
+                None => {
+                    let seq = captura::Secuencia::next_free(SEQUENCE_PATTERN);
+                    println!("Grabando secuencia desde {}", seq.frame_path().display());
+                    Some(seq)
+                }
+            };
+        }
+        // Activan y desactivan cada efecto de post-proceso
+        for (action, efecto) in EFFECT_ACTIONS.into_iter().zip(posproceso::Efecto::ALL) {
+            if input.is_pressed(&rl, action) {
+                post.settings.toggle(efecto);
+            }
+        }
         
-        // Actualizar controles
-        camera_controls.update(&rl);
-        light_controls.update(&rl);
+        // Actualizar controles. Mientras se graba una secuencia cada fotograma avanza un paso fijo,
+        // así el video no depende de lo que tarda guardar cada imagen
+        let dt = if secuencia.is_some() { SEQUENCE_FRAME_TIME } else { rl.get_frame_time().min(MAX_FRAME_TIME) };
+        // Mientras se arrastra una luz el mouse no mueve la cámara
+        let grabbing = light_gizmo.update(&rl, &input, &cam, &mut light_controls);
+        if !grabbing {
+            camera_controls.update(&rl, &input, dt);
+        }
+        light_controls.update(&rl, &input, dt);
         
         // Actualizar posiciones
         camera_controls.update_camera_position(&mut cam);
-        light_controls.update_light_position(&mut light);
+        light_controls.update_light_positions(&mut escena.lights);
+        let shader = if render_mode == RenderMode::Pbr { &mut pbr_shader } else { &mut lighting_shader };
+        shader.update(&escena.lights, cam.position);
 
         // Renderizado
         let mut d = rl.begin_drawing(&thread);
         d.clear_background(escena.background_color);
 
+        // Pasada de profundidad desde cada luz (sólo con shader)
+        if render_mode.uses_shader() {
+            shadow_maps.render(&mut d, &thread, &escena);
+            shader.update_shadows(Some(&shadow_maps));
+        }
+
         // Render a framebuffer
+        // El framebuffer guarda luz lineal: el fondo (sRGB) se pasa a lineal para que se vea igual
         let mut dfb = d.begin_texture_mode(&thread, &mut fb);
-        dfb.clear_background(escena.background_color);
+        dfb.clear_background(tonos::srgb_to_linear_color(escena.background_color));
         let mut d3 = dfb.begin_mode3D(cam);
+        if let Some(skybox) = skybox.as_mut() {
+            skybox.render(&mut d3, &cam);
+        }
 
-    // Renderizar escena con textura
-    escena.render(&mut d3, &light, &cam, show_normals, show_wireframe);
+        // Renderizar escena con textura
+        let options = RenderOptions {
+            mode: render_mode,
+            shader,
+            vertex_shader: &vertex_shader,
+            show_normals,
+            show_wireframe,
+        };
+        escena.render(&mut d3, &cam, options);
         
         drop(d3);
         drop(dfb);
         
         // Mostrar framebuffer en pantalla
-        framebuffer::blit_to_screen(&mut d, &fb);
+        framebuffer::blit_to_screen(&mut d, &fb, &mut tonemap, &mut post);
+
+        // Capturas del fotograma recién terminado (sin la UI)
+        if let Some(path) = screenshot.take() {
+            match captura::save(&path, &fb, &post) {
+                Ok(()) => println!("Captura guardada en {}", path.display()),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if let Some(seq) = secuencia.as_mut() {
+            if let Err(e) = seq.capture(&fb, &post) {
+                eprintln!("{}", e);
+                secuencia = None;
+            } else if seq.is_finished() {
+                println!("Secuencia completa: {} fotogramas", seq.frame);
+                break;
+            }
+        }
         
+        light_gizmo.render(&mut d, cam, &light_controls);
+
         // Renderizar UI solo si está habilitado
         if show_ui {
-            ui::render_ui(&mut d, &camera_controls, &light_controls);
+            let hud = ui::HudInfo {
+                render_mode,
+                shadows: &shadow_maps.settings,
+                tonemap: &tonemap.settings,
+                post: &post.settings,
+                input: &input,
+            };
+            ui::render_ui(&mut d, &camera_controls, &light_controls, &hud);
         }
     }
 }
+
+// Los modos con shader sólo están disponibles si su shader compiló
+fn mode_available(mode: RenderMode, lighting: &shaders::LightingShader, pbr: &shaders::LightingShader) -> bool {
+    match mode {
+        RenderMode::Shader => lighting.is_ready(),
+        RenderMode::Pbr => pbr.is_ready(),
+        _ => true,
+    }
+}
+
+// Renderiza el primer fotograma de la escena con el trazador por CPU y lo guarda en disco
+fn render_headless(desc: &SceneFile, output: &str, spp: Option<u32>) {
+    let mut escena = Escena::from_description(desc, Vec::new());
+    let textures: Vec<_> = desc.texture_paths().iter()
+        .map(|path| archivo_malla::load_texture_image(path).expect("No se pudo cargar la textura"))
+        .collect();
+
+    // Misma cámara y luces que el primer fotograma de la ventana
+    let mut cam = desc.make_camera(escena.focus_point());
+    CameraControls::new(cam.position, cam.target).update_camera_position(&mut cam);
+
+    desc.make_light_controls(&escena.lights).update_light_positions(&mut escena.lights);
+
+    let mut settings = trazador::TraceSettings::default();
+    if let Some(spp) = spp {
+        settings.samples_per_pixel = spp;
+    }
+
+    if let Err(e) = trazador::render_to_file(&escena, &cam, &textures, &settings, output) {
+        eprintln!("No se pudo guardar la imagen {}: {}", output, e);
+        std::process::exit(1);
+    }
+    println!("Imagen guardada en {}", output);
+}
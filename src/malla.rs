@@ -0,0 +1,182 @@
+use raylib::prelude::*;
+use crate::light;
+use crate::material::Material;
+use crate::shaders::LightingShader;
+use crate::transform::Transform;
+
+// Largo de las líneas de normales (más cortas que en el cubo porque las mallas suelen ser densas)
+const NORMAL_LENGTH: f32 = 0.3;
+
+#[derive(Clone, Copy)]
+pub struct Vertice {
+    pub pos: Vector3,
+    pub normal: Vector3,
+    pub uv: Vector2,
+}
+
+// Triángulo en espacio mundo para el trazador: normal de la cara (de qué lado se ve), vértices con
+// su normal y UV, y la textura propia si reemplaza a la del material (las caras del cubo)
+#[derive(Clone, Copy)]
+pub struct TrianguloUV {
+    pub normal: Vector3,
+    pub verts: [Vertice; 3],
+    pub texture: Option<usize>,
+}
+
+// Malla de triángulos cargada de un archivo (ver archivo_malla.rs).
+// Después de `transformed` los vértices y normales están en espacio mundo, igual que en Cubo
+#[derive(Clone)]
+pub struct Malla {
+    pub triangles: Vec<[Vertice; 3]>,
+}
+
+impl Malla {
+    pub fn new(triangles: Vec<[Vertice; 3]>) -> Self {
+        Self { triangles }
+    }
+
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let triangles = self.triangles.iter()
+            .map(|tri| tri.map(|v| Vertice {
+                pos: transform.transform_point(v.pos),
+                normal: transform.transform_normal(v.normal),
+                uv: v.uv,
+            }))
+            .collect();
+        Self { triangles }
+    }
+
+    // Caja envolvente (mínimo, máximo)
+    pub fn bounds(&self) -> (Vector3, Vector3) {
+        let mut min = Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for v in self.triangles.iter().flatten() {
+            min = Vector3::new(min.x.min(v.pos.x), min.y.min(v.pos.y), min.z.min(v.pos.z));
+            max = Vector3::new(max.x.max(v.pos.x), max.y.max(v.pos.y), max.z.max(v.pos.z));
+        }
+        (min, max)
+    }
+
+    pub fn center(&self) -> Vector3 {
+        let (min, max) = self.bounds();
+        (min + max) * 0.5
+    }
+
+    fn face_normal(tri: &[Vertice; 3]) -> Vector3 {
+        (tri[1].pos - tri[0].pos).cross(tri[2].pos - tri[0].pos).normalized()
+    }
+
+    fn face_center(tri: &[Vertice; 3]) -> Vector3 {
+        (tri[0].pos + tri[1].pos + tri[2].pos) / 3.0
+    }
+
+    // Iluminación en CPU en cada vértice con su propia normal (Gouraud), así las primitivas curvas
+    // y las mallas con normales suaves no se ven facetadas; la textura multiplica el color al muestrear
+    pub fn render_textured(&self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                           lights: &[light::Light], camera_pos: Vector3, material: &Material) {
+        let texture = material.texture.and_then(|t| textures.get(t));
+        unsafe {
+            raylib::ffi::rlSetTexture(texture.map_or_else(|| raylib::ffi::rlGetTextureIdDefault(), |t| t.id));
+            raylib::ffi::rlBegin(raylib::ffi::RL_TRIANGLES as i32);
+            for tri in &self.triangles {
+                for v in tri {
+                    let color = light::calculate_realistic_lighting(v.normal, v.pos, lights, camera_pos, material);
+                    raylib::ffi::rlColor4ub(color.r, color.g, color.b, color.a);
+                    raylib::ffi::rlNormal3f(v.normal.x, v.normal.y, v.normal.z);
+                    raylib::ffi::rlTexCoord2f(v.uv.x, v.uv.y);
+                    raylib::ffi::rlVertex3f(v.pos.x, v.pos.y, v.pos.z);
+                }
+            }
+            raylib::ffi::rlEnd();
+            raylib::ffi::rlSetTexture(0);
+        }
+    }
+
+    // Con el shader por fragmento, usando las normales suaves de los vértices
+    pub fn render_shaded(&self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                         shader: &mut LightingShader, material: &Material) {
+        let texture = material.texture.and_then(|t| textures.get(t));
+        shader.set_material(material, textures);
+        unsafe {
+            shader.begin();
+            raylib::ffi::rlSetTexture(texture.map_or_else(|| raylib::ffi::rlGetTextureIdDefault(), |t| t.id));
+            raylib::ffi::rlBegin(raylib::ffi::RL_TRIANGLES as i32);
+            raylib::ffi::rlColor4ub(255, 255, 255, 255);
+            for tri in &self.triangles {
+                for v in tri {
+                    raylib::ffi::rlNormal3f(v.normal.x, v.normal.y, v.normal.z);
+                    raylib::ffi::rlTexCoord2f(v.uv.x, v.uv.y);
+                    raylib::ffi::rlVertex3f(v.pos.x, v.pos.y, v.pos.z);
+                }
+            }
+            raylib::ffi::rlEnd();
+            raylib::ffi::rlSetTexture(0);
+            shader.end();
+        }
+    }
+
+    // Misma sombra proyectada que Cubo::render_shadow_stable
+    pub fn render_shadow_stable(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>,
+                                light: &light::Light, floor_y: f32) {
+        if light.lights_plane_from_above(floor_y) {
+            let shadow_y = floor_y + 0.0001; // Offset muy pequeño para evitar z-fighting
+            let shadow_color = Color::new(0, 0, 0, 100);
+            for tri in &self.triangles {
+                if Self::face_center(tri).y > floor_y + 0.05 { // Solo caras claramente sobre el piso
+                    let [a, b, c] = tri.map(|v| light.project_to_plane(v.pos, shadow_y));
+                    d3.draw_triangle3D(a, b, c, shadow_color);
+                }
+            }
+        }
+    }
+
+    pub fn render_wireframe(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, cam: &Camera3D) {
+        unsafe {
+            raylib::ffi::rlDisableDepthTest();
+            raylib::ffi::rlSetLineWidth(1.0);
+        }
+
+        for tri in &self.triangles {
+            for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+                let (p1, p2) = (tri[i].pos, tri[j].pos);
+                let offset = (cam.position - ((p1 + p2) * 0.5)).normalized() * 0.001;
+                d3.draw_line_3D(p1 + offset, p2 + offset, Color::WHITE);
+            }
+        }
+
+        unsafe {
+            raylib::ffi::rlEnableDepthTest();
+        }
+    }
+
+    // Normal de cada triángulo desde su centro
+    pub fn render_normals(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+        for tri in &self.triangles {
+            let ctri = Self::face_center(tri);
+            d3.draw_line_3D(ctri, ctri + Self::face_normal(tri) * NORMAL_LENGTH, Color::YELLOW);
+        }
+    }
+
+    // Sólo geometría, para la pasada de profundidad del shadow map
+    pub fn render_depth(&self) {
+        unsafe {
+            raylib::ffi::rlBegin(raylib::ffi::RL_TRIANGLES as i32);
+            for v in self.triangles.iter().flatten() {
+                raylib::ffi::rlVertex3f(v.pos.x, v.pos.y, v.pos.z);
+            }
+            raylib::ffi::rlEnd();
+        }
+    }
+
+    pub fn triangles(&self) -> Vec<(Vector3, Vector3, Vector3)> {
+        self.triangles.iter().map(|t| (t[0].pos, t[1].pos, t[2].pos)).collect()
+    }
+
+    // Triángulos con UV en espacio mundo; usan la textura del material. Las normales de los vértices
+    // permiten interpolarlas en superficies curvas
+    pub fn textured_triangles(&self) -> Vec<TrianguloUV> {
+        self.triangles.iter().map(|t| TrianguloUV { normal: Self::face_normal(t), verts: *t, texture: None }).collect()
+    }
+}
+
+// Módulo para mallas de triángulos
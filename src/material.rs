@@ -0,0 +1,49 @@
+use raylib::prelude::*;
+
+// Propiedades de superficie de cada objeto; las usan tanto la iluminación por CPU como los shaders.
+// ambient/diffuse/specular/shininess son del modelo Phong; metallic/roughness del modelo PBR
+#[derive(Clone, Copy)]
+pub struct Material {
+    pub albedo: Vector3,        // Color base (0..1), se multiplica por la textura si la hay
+    pub texture: Option<usize>, // Índice en Escena::textures
+    pub ambient: f32,           // Fuerza de la luz ambiente
+    pub diffuse: f32,           // Fuerza difusa
+    pub specular: f32,          // Fuerza especular
+    pub shininess: f32,         // Exponente del especular
+    pub emissive: Vector3,      // Luz propia, se suma sin depender de las luces
+    pub metallic: f32,          // PBR: 0 = dieléctrico, 1 = metal
+    pub roughness: f32,         // PBR: 0 = espejo, 1 = totalmente mate
+    pub normal_map: Option<usize>, // Mapa de normales en espacio tangente (índice en Escena::textures)
+    pub height_map: Option<usize>, // Mapa de alturas para parallax (blanco = alto)
+    pub normal_strength: f32,   // Escala la inclinación del mapa de normales
+    pub parallax_scale: f32,    // Profundidad del parallax en unidades de UV
+}
+
+impl Material {
+    pub fn new(albedo: Vector3) -> Self {
+        Self { albedo, ..Self::default() }
+    }
+}
+
+impl Default for Material {
+    // Valores que antes eran las constantes globales de colores.rs
+    fn default() -> Self {
+        Self {
+            albedo: Vector3::new(0.9, 0.4, 0.3),
+            texture: None,
+            ambient: 0.05,
+            diffuse: 2.0,
+            specular: 0.6,
+            shininess: 32.0,
+            emissive: Vector3::zero(),
+            metallic: 0.0,
+            roughness: 0.5,
+            normal_map: None,
+            height_map: None,
+            normal_strength: 1.0,
+            parallax_scale: 0.04,
+        }
+    }
+}
+
+// Módulo para materiales
@@ -0,0 +1,90 @@
+use raylib::prelude::*;
+use crate::{cubo::Cubo, light::Light, malla::{Malla, TrianguloUV}, material::Material, shaders::LightingShader};
+
+// Geometría de un objeto de la escena
+pub enum Forma {
+    Cubo(Box<Cubo>), // En caja: es mucho más grande que las otras variantes
+    Malla(Malla),
+}
+
+// Objeto renderizable: forma más material (con textura opcional).
+// Las formas reciben todas las texturas de la escena: el cubo puede usar una distinta en cada cara
+pub struct Objeto {
+    pub forma: Forma,
+    pub material: Material,
+}
+
+impl Objeto {
+    pub fn new(forma: Forma, material: Material) -> Self {
+        Self { forma, material }
+    }
+
+    pub fn center(&self) -> Vector3 {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.center(),
+            Forma::Malla(malla) => malla.center(),
+        }
+    }
+
+    pub fn render_textured(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>,
+                           textures: &[Texture2D], lights: &[Light], camera_pos: Vector3) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_textured(d3, textures, lights, camera_pos, &self.material),
+            Forma::Malla(malla) => malla.render_textured(d3, textures, lights, camera_pos, &self.material),
+        }
+    }
+
+    pub fn render_shaded(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>,
+                         textures: &[Texture2D], shader: &mut LightingShader) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_shaded(d3, textures, shader, &self.material),
+            Forma::Malla(malla) => malla.render_shaded(d3, textures, shader, &self.material),
+        }
+    }
+
+    pub fn render_shadow_stable(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>,
+                                light: &Light, floor_y: f32) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_shadow_stable(d3, light, floor_y),
+            Forma::Malla(malla) => malla.render_shadow_stable(d3, light, floor_y),
+        }
+    }
+
+    pub fn render_wireframe(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, cam: &Camera3D) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_wireframe(d3, cam),
+            Forma::Malla(malla) => malla.render_wireframe(d3, cam),
+        }
+    }
+
+    pub fn render_normals(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_normals(d3),
+            Forma::Malla(malla) => malla.render_normals(d3),
+        }
+    }
+
+    pub fn render_depth(&self) {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.render_depth(),
+            Forma::Malla(malla) => malla.render_depth(),
+        }
+    }
+
+    pub fn triangles(&self) -> Vec<(Vector3, Vector3, Vector3)> {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.triangles(),
+            Forma::Malla(malla) => malla.triangles(),
+        }
+    }
+
+    // Triángulos con UV y, si la tienen, una textura que reemplaza a la del material
+    pub fn textured_triangles(&self) -> Vec<TrianguloUV> {
+        match &self.forma {
+            Forma::Cubo(cubo) => cubo.textured_triangles(),
+            Forma::Malla(malla) => malla.textured_triangles(),
+        }
+    }
+}
+
+// Módulo para objetos de la escena
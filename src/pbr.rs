@@ -0,0 +1,79 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+// Reflectancia a incidencia normal de los dieléctricos
+const DIELECTRIC_F0: f32 = 0.04;
+
+// Mismo modelo que resources/shaders/pbr.fs: Cook-Torrance con distribución GGX,
+// geometría Smith-Schlick y Fresnel de Schlick, todo en espacio de color lineal.
+// Devuelve BRDF * N·L; se multiplica por la radiancia que llega de la luz.
+pub fn cook_torrance(n: Vector3, v: Vector3, l: Vector3, albedo: Vector3, metallic: f32, roughness: f32) -> Vector3 {
+    let ndotl = n.dot(l).max(0.0);
+    let ndotv = n.dot(v).max(1e-4);
+    if ndotl <= 0.0 {
+        return Vector3::zero();
+    }
+
+    let h = (v + l).normalized();
+    let ndoth = n.dot(h).max(0.0);
+    let vdoth = v.dot(h).max(0.0);
+
+    // Rugosidad mínima para que el brillo de luces puntuales no sea infinito
+    let roughness = roughness.clamp(0.04, 1.0);
+    let metallic = metallic.clamp(0.0, 1.0);
+    let f0 = Vector3::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0).lerp(albedo, metallic);
+
+    let d = distribution_ggx(ndoth, roughness);
+    let g = geometry_smith(ndotv, ndotl, roughness);
+    let f = fresnel_schlick(vdoth, f0);
+
+    let specular = f * (d * g / (4.0 * ndotv * ndotl));
+    // Lo que no se refleja se difunde; los metales no tienen difuso
+    let kd = (Vector3::one() - f) * (1.0 - metallic);
+    let diffuse = kd * albedo / PI;
+
+    (diffuse + specular) * ndotl
+}
+
+// Reflectancia especular media frente a un entorno (aproximación analítica de Karis de la
+// integral de la BRDF); la misma que envBRDF en pbr.fs
+pub fn env_brdf(albedo: Vector3, metallic: f32, roughness: f32, ndotv: f32) -> Vector3 {
+    let f0 = Vector3::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0).lerp(albedo, metallic.clamp(0.0, 1.0));
+    let r = roughness.clamp(0.04, 1.0);
+    let k = (r * -1.0 + 1.0, r * -0.0275 + 0.0425, r * -0.572 + 1.04, r * 0.022 - 0.04);
+    let a004 = (k.0 * k.0).min((-9.28 * ndotv).exp2()) * k.0 + k.1;
+    let (a, b) = (-1.04 * a004 + k.2, 1.04 * a004 + k.3);
+    f0 * a + Vector3::new(b, b, b)
+}
+
+fn distribution_ggx(ndoth: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let denom = ndoth * ndoth * (a2 - 1.0) + 1.0;
+    a2 / (PI * denom * denom)
+}
+
+fn geometry_schlick_ggx(ndotx: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let k = r * r / 8.0;
+    ndotx / (ndotx * (1.0 - k) + k)
+}
+
+fn geometry_smith(ndotv: f32, ndotl: f32, roughness: f32) -> f32 {
+    geometry_schlick_ggx(ndotv, roughness) * geometry_schlick_ggx(ndotl, roughness)
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: Vector3) -> Vector3 {
+    f0 + (Vector3::one() - f0) * (1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+// Conversión entre sRGB (texturas, imagen final) y lineal (cálculo de la iluminación)
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Módulo para sombreado PBR (metallic / roughness)
@@ -1,49 +1,239 @@
 use raylib::prelude::*;
+use std::cell::RefCell;
+use crate::{light::{self, Light}, material::Material, shaders::LightingShader};
+
+// Cuadrícula superpuesta al piso; se desvanece con la distancia a la cámara
+pub struct Cuadricula {
+    pub cells: u32,          // Celdas por lado
+    pub color: Color,
+    pub fade_distance: f32,  // A esta distancia de la cámara ya no se ve
+}
+
+impl Default for Cuadricula {
+    fn default() -> Self {
+        Self { cells: 10, color: Color::new(60, 60, 70, 100), fade_distance: 15.0 }
+    }
+}
+
+impl Cuadricula {
+    fn alpha_at(&self, distance: f32) -> u8 {
+        let t = (distance / self.fade_distance).clamp(0.0, 1.0);
+        (self.color.a as f32 * (1.0 - t * t)) as u8
+    }
+}
+
+// Tramos de cada línea de la cuadrícula por celda (el alpha se interpola entre sus extremos)
+const GRID_SEGMENTS_PER_CELL: u32 = 4;
+// Con las luces en movimiento el piso trazado se renueva por filas: una pasada completa cada tantos fotogramas
+const RAYTRACED_REFRESH_FRAMES: usize = 8;
+
+// Colores de las celdas trazadas junto a lo que los produjo; no dependen de la cámara.
+// Si cambian los objetos o la resolución se trazan todas; si cambian las luces (que por defecto
+// orbitan) se vuelven a trazar unas filas por fotograma, así el costo por fotograma baja
+// RAYTRACED_REFRESH_FRAMES veces y con las luces quietas no se traza nada
+struct CeldasTrazadas {
+    lights: Vec<Light>, // Las últimas vistas
+    triangles: Vec<(Vector3, Vector3, Vector3)>,
+    resolution: usize,
+    colors: Vec<Color>,
+    stale_rows: usize, // Filas trazadas con luces anteriores a `lights`
+    next_row: usize,   // Próxima fila a renovar
+}
 
 pub struct Piso {
     pub y_position: f32,
     pub size: f32,
+    pub material: Material,
+    pub tiling: f32, // Veces que se repite la textura a lo largo de cada lado
+    pub grid: Option<Cuadricula>,
+    raytraced: RefCell<Option<CeldasTrazadas>>,
 }
 
 impl Piso {
-    pub fn new(y_position: f32, size: f32) -> Self {
-        Self { y_position, size }
+    pub fn new(y_position: f32, size: f32, material: Material) -> Self {
+        Self { y_position, size, material, tiling: 1.0, grid: Some(Cuadricula::default()), raytraced: RefCell::new(None) }
+    }
+
+    // Material por defecto del piso: gris oscuro mate
+    pub fn default_material() -> Material {
+        Material {
+            specular: 0.1,
+            shininess: 8.0,
+            ..Material::new(Vector3::new(40.0 / 255.0, 40.0 / 255.0, 45.0 / 255.0))
+        }
+    }
+
+    // UV de un punto del piso, con la textura repetida `tiling` veces
+    pub fn uv_at(&self, x: f32, z: f32) -> Vector2 {
+        Vector2::new((x / self.size + 0.5) * self.tiling, (z / self.size + 0.5) * self.tiling)
+    }
+
+    fn texture_id(&self, textures: &[Texture2D]) -> u32 {
+        match self.material.texture.and_then(|i| textures.get(i)) {
+            Some(texture) => texture.id,
+            None => unsafe { raylib::ffi::rlGetTextureIdDefault() },
+        }
+    }
+
+    // Fallback por CPU: piso teselado con cada celda iluminada en su centro por todas las luces
+    pub fn render(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                  lights: &[Light], camera_pos: Vector3, resolution: usize) {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        self.render_cells(d3, textures, resolution, |_, center| {
+            light::calculate_realistic_lighting(up, center, lights, camera_pos, &self.material)
+        });
+        self.render_grid(camera_pos);
     }
 
-    pub fn render(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
-        // Dibujar piso base
-        let floor_color = Color::new(40, 40, 45, 255);
-        d3.draw_plane(
-            Vector3::new(0.0, self.y_position, 0.0), 
-            Vector2::new(self.size, self.size), 
-            floor_color
+    // Piso iluminado por el shader para que reciba las sombras del shadow map
+    pub fn render_shaded(&self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                         shader: &mut LightingShader, camera_pos: Vector3) {
+        let h = self.size / 2.0;
+        let y = self.y_position;
+        shader.set_material(&self.material, textures);
+        shader.begin();
+        unsafe {
+            raylib::ffi::rlSetTexture(self.texture_id(textures));
+            raylib::ffi::rlBegin(raylib::ffi::RL_QUADS as i32);
+            raylib::ffi::rlColor4ub(255, 255, 255, 255);
+            raylib::ffi::rlNormal3f(0.0, 1.0, 0.0);
+            for (x, z) in [(-h, -h), (-h, h), (h, h), (h, -h)] {
+                let uv = self.uv_at(x, z);
+                raylib::ffi::rlTexCoord2f(uv.x, uv.y);
+                raylib::ffi::rlVertex3f(x, y, z);
+            }
+            raylib::ffi::rlEnd();
+            raylib::ffi::rlSetTexture(0);
+        }
+        shader.end();
+
+        self.render_grid(camera_pos);
+    }
+
+    // Los dos triángulos del plano del piso
+    pub fn triangles(&self) -> Vec<(Vector3, Vector3, Vector3)> {
+        let h = self.size / 2.0;
+        let y = self.y_position;
+        let (a, b, c, e) = (
+            Vector3::new(-h, y, -h),
+            Vector3::new(-h, y, h),
+            Vector3::new(h, y, h),
+            Vector3::new(h, y, -h),
         );
-        
-        // Dibujar cuadrícula
-        self.render_grid(d3);
-    }
-
-    fn render_grid(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>) {
-        let grid_size = 10;
-        let grid_spacing = self.size / grid_size as f32;
-        let grid_color = Color::new(60, 60, 70, 100);
-        
-        for i in 0..=grid_size {
-            let pos = -self.size/2.0 + i as f32 * grid_spacing;
-            
-            // Líneas horizontales
-            d3.draw_line_3D(
-                Vector3::new(-self.size/2.0, self.y_position + 0.001, pos),
-                Vector3::new(self.size/2.0, self.y_position + 0.001, pos),
-                grid_color
-            );
-            
-            // Líneas verticales
-            d3.draw_line_3D(
-                Vector3::new(pos, self.y_position + 0.001, -self.size/2.0),
-                Vector3::new(pos, self.y_position + 0.001, self.size/2.0),
-                grid_color
-            );
+        vec![(a, b, c), (a, c, e)]
+    }
+
+    // Piso teselado en `resolution` x `resolution` celdas; cada celda se sombrea en su centro
+    // con shade_with_shadows (todas las luces) contra todos los triángulos de la escena (sombras duras trazadas).
+    // Los colores se guardan entre fotogramas (ver CeldasTrazadas)
+    pub fn render_raytraced(&self, d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                            lights: &[Light], triangles: &[(Vector3, Vector3, Vector3)], camera_pos: Vector3,
+                            resolution: usize) {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let shade = |index: usize| light::shade_with_shadows(up, self.cell_center(index, resolution), lights, &self.material, triangles);
+
+        let mut cache = self.raytraced.borrow_mut();
+        let valid = cache.as_ref().is_some_and(|c| c.resolution == resolution && c.triangles == triangles);
+        if !valid {
+            *cache = Some(CeldasTrazadas {
+                lights: lights.to_vec(),
+                triangles: triangles.to_vec(),
+                resolution,
+                colors: (0..resolution * resolution).map(shade).collect(),
+                stale_rows: 0,
+                next_row: 0,
+            });
+        }
+        let cells = cache.as_mut().expect("recién calculadas");
+        if cells.lights != lights {
+            cells.lights = lights.to_vec();
+            cells.stale_rows = resolution;
+        }
+        let rows = cells.stale_rows.min(resolution.div_ceil(RAYTRACED_REFRESH_FRAMES));
+        for _ in 0..rows {
+            let row = cells.next_row * resolution..(cells.next_row + 1) * resolution;
+            for index in row {
+                cells.colors[index] = shade(index);
+            }
+            cells.next_row = (cells.next_row + 1) % resolution;
+        }
+        cells.stale_rows -= rows;
+
+        let colors = &cells.colors;
+        self.render_cells(d3, textures, resolution, |index, _| colors[index]);
+        self.render_grid(camera_pos);
+    }
+
+    // Centro de la celda `index` (por filas a lo largo de X) de la teselación en `resolution` x `resolution`
+    fn cell_center(&self, index: usize, resolution: usize) -> Vector3 {
+        let step = self.size / resolution as f32;
+        let h = self.size / 2.0;
+        let (i, j) = (index % resolution, index / resolution);
+        Vector3::new(-h + (i as f32 + 0.5) * step, self.y_position, -h + (j as f32 + 0.5) * step)
+    }
+
+    // Celdas texturizadas con el color que da `cell_color` para su índice y su centro (la textura lo multiplica)
+    fn render_cells(&self, _d3: &mut RaylibMode3D<RaylibTextureMode<RaylibDrawHandle>>, textures: &[Texture2D],
+                    resolution: usize, cell_color: impl Fn(usize, Vector3) -> Color) {
+        let step = self.size / resolution as f32;
+        let h = self.size / 2.0;
+        let y = self.y_position;
+
+        unsafe {
+            raylib::ffi::rlSetTexture(self.texture_id(textures));
+            raylib::ffi::rlBegin(raylib::ffi::RL_QUADS as i32);
+            raylib::ffi::rlNormal3f(0.0, 1.0, 0.0);
+            for j in 0..resolution {
+                for i in 0..resolution {
+                    let x0 = -h + i as f32 * step;
+                    let z0 = -h + j as f32 * step;
+                    let (x1, z1) = (x0 + step, z0 + step);
+
+                    let index = j * resolution + i;
+                    let color = cell_color(index, self.cell_center(index, resolution));
+                    raylib::ffi::rlColor4ub(color.r, color.g, color.b, color.a);
+                    for (x, z) in [(x0, z0), (x0, z1), (x1, z1), (x1, z0)] {
+                        let uv = self.uv_at(x, z);
+                        raylib::ffi::rlTexCoord2f(uv.x, uv.y);
+                        raylib::ffi::rlVertex3f(x, y, z);
+                    }
+                }
+            }
+            raylib::ffi::rlEnd();
+            raylib::ffi::rlSetTexture(0);
+        }
+    }
+
+    // Líneas partidas en tramos: cada extremo lleva el alpha según su distancia a la cámara
+    fn render_grid(&self, camera_pos: Vector3) {
+        let Some(grid) = &self.grid else {
+            return;
+        };
+        let cells = grid.cells.max(1);
+        let spacing = self.size / cells as f32;
+        let segments = cells * GRID_SEGMENTS_PER_CELL;
+        let segment_len = self.size / segments as f32;
+        let h = self.size / 2.0;
+        let y = self.y_position + 0.001;
+
+        unsafe {
+            raylib::ffi::rlBegin(raylib::ffi::RL_LINES as i32);
+            for i in 0..=cells {
+                let pos = -h + i as f32 * spacing;
+                for s in 0..segments {
+                    let (a, b) = (-h + s as f32 * segment_len, -h + (s + 1) as f32 * segment_len);
+                    // Líneas a lo largo de X y a lo largo de Z
+                    for v in [
+                        Vector3::new(a, y, pos), Vector3::new(b, y, pos),
+                        Vector3::new(pos, y, a), Vector3::new(pos, y, b),
+                    ] {
+                        let alpha = grid.alpha_at((v - camera_pos).length());
+                        raylib::ffi::rlColor4ub(grid.color.r, grid.color.g, grid.color.b, alpha);
+                        raylib::ffi::rlVertex3f(v.x, v.y, v.z);
+                    }
+                }
+            }
+            raylib::ffi::rlEnd();
         }
     }
 }
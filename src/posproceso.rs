@@ -0,0 +1,381 @@
+use raylib::prelude::*;
+use crate::framebuffer::{self, Framebuffer, TonemapPass};
+
+const BLOOM_BRIGHT_FS: &str = "resources/shaders/bloom_brillo.fs";
+const BLOOM_BLUR_FS: &str = "resources/shaders/bloom_desenfoque.fs";
+const BLOOM_FS: &str = "resources/shaders/bloom.fs";
+const DOF_FS: &str = "resources/shaders/profundidad_campo.fs";
+const FXAA_FS: &str = "resources/shaders/fxaa.fs";
+const LUT_FS: &str = "resources/shaders/lut.fs";
+const CHROMATIC_FS: &str = "resources/shaders/aberracion.fs";
+const VIGNETTE_FS: &str = "resources/shaders/vineta.fs";
+
+// Unidad de textura de la imagen extra de una pasada (bloom, profundidad o LUT); la 0 es la de entrada
+const EXTRA_SLOT: i32 = 1;
+// Planos de recorte por defecto de rlgl, para pasar la profundidad a distancia
+const CAMERA_NEAR: f32 = 0.01;
+const CAMERA_FAR: f32 = 1000.0;
+
+// Efectos de la cadena; `order` en PostSettings decide en qué orden se aplican
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Efecto {
+    Bloom,
+    DepthOfField,
+    Fxaa,
+    ColorGrading,
+    ChromaticAberration,
+    Vignette,
+}
+
+impl Efecto {
+    // Orden por defecto, que también es el de las teclas F1..F6
+    pub const ALL: [Efecto; 6] = [
+        Efecto::Bloom,
+        Efecto::DepthOfField,
+        Efecto::Fxaa,
+        Efecto::ColorGrading,
+        Efecto::ChromaticAberration,
+        Efecto::Vignette,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Efecto::Bloom => "Bloom",
+            Efecto::DepthOfField => "Profundidad de campo",
+            Efecto::Fxaa => "FXAA",
+            Efecto::ColorGrading => "LUT",
+            Efecto::ChromaticAberration => "Aberración cromática",
+            Efecto::Vignette => "Viñeta",
+        }
+    }
+
+    // Bloom y profundidad de campo trabajan sobre la luz lineal, antes del tonemap;
+    // el resto sobre la imagen ya en sRGB
+    pub fn is_hdr(self) -> bool {
+        matches!(self, Efecto::Bloom | Efecto::DepthOfField)
+    }
+}
+
+#[derive(Clone)]
+pub struct Bloom {
+    pub enabled: bool,
+    pub threshold: f32, // Luz a partir de la cual brilla
+    pub intensity: f32,
+    pub radius: f32, // Escala del desenfoque
+}
+
+#[derive(Clone)]
+pub struct DepthOfField {
+    pub enabled: bool,
+    pub focus_distance: f32, // Distancia a la cámara que queda nítida
+    pub focus_range: f32, // Ancho de la zona nítida
+    pub max_blur: f32, // Radio máximo del desenfoque en píxeles
+}
+
+#[derive(Clone)]
+pub struct Fxaa {
+    pub enabled: bool,
+    pub span_max: f32, // Largo máximo del borde que se suaviza, en píxeles
+}
+
+#[derive(Clone)]
+pub struct ColorGrading {
+    pub enabled: bool,
+    pub lut: Option<String>, // Tira de N cortes de NxN (p. ej. 256x16); sin LUT no hace nada
+    pub strength: f32, // Mezcla con la imagen original
+}
+
+#[derive(Clone)]
+pub struct ChromaticAberration {
+    pub enabled: bool,
+    pub strength: f32, // Separación de rojo y azul en el borde, en unidades de UV
+}
+
+#[derive(Clone)]
+pub struct Vignette {
+    pub enabled: bool,
+    pub intensity: f32,
+    pub radius: f32, // Distancia al centro donde empieza a oscurecer
+    pub softness: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self { enabled: false, threshold: 1.0, intensity: 0.5, radius: 1.0 }
+    }
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self { enabled: false, focus_distance: 6.0, focus_range: 2.0, max_blur: 8.0 }
+    }
+}
+
+impl Default for Fxaa {
+    fn default() -> Self {
+        Self { enabled: false, span_max: 8.0 }
+    }
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self { enabled: false, lut: None, strength: 1.0 }
+    }
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        Self { enabled: false, strength: 0.005 }
+    }
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self { enabled: false, intensity: 0.5, radius: 0.75, softness: 0.45 }
+    }
+}
+
+// Parámetros de todos los efectos y el orden en que se encadenan
+#[derive(Clone)]
+pub struct PostSettings {
+    pub order: Vec<Efecto>,
+    pub bloom: Bloom,
+    pub depth_of_field: DepthOfField,
+    pub fxaa: Fxaa,
+    pub color_grading: ColorGrading,
+    pub chromatic_aberration: ChromaticAberration,
+    pub vignette: Vignette,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        Self {
+            order: Efecto::ALL.to_vec(),
+            bloom: Bloom::default(),
+            depth_of_field: DepthOfField::default(),
+            fxaa: Fxaa::default(),
+            color_grading: ColorGrading::default(),
+            chromatic_aberration: ChromaticAberration::default(),
+            vignette: Vignette::default(),
+        }
+    }
+}
+
+impl PostSettings {
+    pub fn is_enabled(&self, efecto: Efecto) -> bool {
+        *self.enabled_flag(efecto)
+    }
+
+    pub fn toggle(&mut self, efecto: Efecto) {
+        let flag = self.enabled_flag_mut(efecto);
+        *flag = !*flag;
+    }
+
+    fn enabled_flag(&self, efecto: Efecto) -> &bool {
+        match efecto {
+            Efecto::Bloom => &self.bloom.enabled,
+            Efecto::DepthOfField => &self.depth_of_field.enabled,
+            Efecto::Fxaa => &self.fxaa.enabled,
+            Efecto::ColorGrading => &self.color_grading.enabled,
+            Efecto::ChromaticAberration => &self.chromatic_aberration.enabled,
+            Efecto::Vignette => &self.vignette.enabled,
+        }
+    }
+
+    fn enabled_flag_mut(&mut self, efecto: Efecto) -> &mut bool {
+        match efecto {
+            Efecto::Bloom => &mut self.bloom.enabled,
+            Efecto::DepthOfField => &mut self.depth_of_field.enabled,
+            Efecto::Fxaa => &mut self.fxaa.enabled,
+            Efecto::ColorGrading => &mut self.color_grading.enabled,
+            Efecto::ChromaticAberration => &mut self.chromatic_aberration.enabled,
+            Efecto::Vignette => &mut self.vignette.enabled,
+        }
+    }
+
+    // Efectos activos en el orden en que se aplican: primero los de luz lineal, luego los de pantalla
+    pub fn active(&self) -> (Vec<Efecto>, Vec<Efecto>) {
+        self.order.iter().copied().filter(|&e| self.is_enabled(e)).partition(|e| e.is_hdr())
+    }
+}
+
+// Una pasada de la cadena
+#[derive(Clone, Copy)]
+enum Etapa {
+    Efecto(Efecto),
+    Tonemap,
+}
+
+// Shaders y render targets de la cadena de post-proceso, entre el framebuffer y la pantalla
+pub struct PostProceso {
+    pub settings: PostSettings,
+    targets: [RenderTexture2D; 2], // Ida y vuelta entre pasadas
+    output: RenderTexture2D, // Imagen final en sRGB de 8 bits, la que se copia a pantalla y se captura
+    bloom_targets: [RenderTexture2D; 2], // Media resolución
+    lut: Option<Texture2D>,
+    bloom_bright: Shader,
+    bloom_blur: Shader,
+    bloom: Shader,
+    depth_of_field: Shader,
+    fxaa: Shader,
+    color_grading: Shader,
+    chromatic_aberration: Shader,
+    vignette: Shader,
+}
+
+impl PostProceso {
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, settings: PostSettings, width: i32, height: i32) -> Self {
+        let target = |w, h| framebuffer::load_target(w, h, framebuffer::HDR_FORMAT, false)
+            .expect("No se pudo crear el render target del post-proceso");
+        let lut = settings.color_grading.lut.as_ref().and_then(|path| match rl.load_texture(thread, path) {
+            Ok(texture) => {
+                texture.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+                Some(texture)
+            }
+            Err(e) => {
+                eprintln!("No se pudo cargar la LUT {}: {}", path, e);
+                None
+            }
+        });
+
+        let mut load = |fragment: &str| {
+            let mut shader = rl.load_shader(thread, None, Some(fragment));
+            // Las imágenes extra siempre van en EXTRA_SLOT; sólo un sampler por shader la usa
+            for name in ["bloomTexture", "depthTexture", "lutTexture"] {
+                let loc = shader.get_shader_location(name);
+                shader.set_shader_value(loc, EXTRA_SLOT);
+            }
+            shader
+        };
+        let mut post = Self {
+            targets: [target(width, height), target(width, height)],
+            output: framebuffer::load_target(width, height, framebuffer::LDR_FORMAT, false)
+                .expect("No se pudo crear el render target del post-proceso"),
+            bloom_targets: [target(width / 2, height / 2), target(width / 2, height / 2)],
+            lut,
+            bloom_bright: load(BLOOM_BRIGHT_FS),
+            bloom_blur: load(BLOOM_BLUR_FS),
+            bloom: load(BLOOM_FS),
+            depth_of_field: load(DOF_FS),
+            fxaa: load(FXAA_FS),
+            color_grading: load(LUT_FS),
+            chromatic_aberration: load(CHROMATIC_FS),
+            vignette: load(VIGNETTE_FS),
+            settings,
+        };
+        let full = Vector2::new(width as f32, height as f32);
+        set_uniform(&mut post.depth_of_field, "resolution", full);
+        set_uniform(&mut post.fxaa, "resolution", full);
+        set_uniform(&mut post.bloom_blur, "resolution", full / 2.0);
+        set_uniform(&mut post.depth_of_field, "cameraNear", CAMERA_NEAR);
+        set_uniform(&mut post.depth_of_field, "cameraFar", CAMERA_FAR);
+        if let Some(lut) = &post.lut {
+            set_uniform(&mut post.color_grading, "lutSize", lut.height as f32);
+        }
+        post
+    }
+
+    // Aplica los efectos activos y el tonemap; la última pasada escribe en `output`, que se copia a pantalla
+    pub fn render(&mut self, d: &mut RaylibDrawHandle, fb: &Framebuffer, tonemap: &mut TonemapPass) {
+        let (hdr, display) = self.settings.active();
+        let stages: Vec<Etapa> = hdr.into_iter().map(Etapa::Efecto)
+            .chain(std::iter::once(Etapa::Tonemap))
+            .chain(display.into_iter().map(Etapa::Efecto))
+            .collect();
+
+        let depth = fb.as_ref().depth.id;
+        let mut source = *fb.texture().as_ref();
+        for (i, stage) in stages.iter().enumerate() {
+            let target = if i + 1 < stages.len() { *self.targets[i % 2].as_ref() } else { *self.output.as_ref() };
+            match *stage {
+                Etapa::Tonemap => pass(tonemap.prepare(), source, target, None),
+                Etapa::Efecto(efecto) => self.apply(efecto, source, target, depth),
+            }
+            source = target.texture;
+        }
+
+        // La última pasada siempre escribe en `output`: se copia a pantalla sin shader, corrigiendo el flip vertical
+        let tex = self.output.texture();
+        let src = Rectangle::new(0.0, 0.0, tex.width() as f32, -(tex.height() as f32));
+        d.draw_texture_rec(tex, src, Vector2::zero(), Color::WHITE);
+    }
+
+    // Último fotograma tal como se ve en pantalla (ver captura.rs)
+    pub fn output(&self) -> &RenderTexture2D {
+        &self.output
+    }
+
+    fn apply(&mut self, efecto: Efecto, source: raylib::ffi::Texture2D, target: raylib::ffi::RenderTexture2D, depth: u32) {
+        let s = &self.settings;
+        match efecto {
+            Efecto::Bloom => {
+                // Zonas brillantes a media resolución, desenfoque separable y suma sobre la imagen
+                let [a, b] = [*self.bloom_targets[0].as_ref(), *self.bloom_targets[1].as_ref()];
+                set_uniform(&mut self.bloom_bright, "threshold", s.bloom.threshold);
+                pass(&self.bloom_bright, source, a, None);
+                set_uniform(&mut self.bloom_blur, "blurRadius", s.bloom.radius);
+                set_uniform(&mut self.bloom_blur, "direction", Vector2::new(1.0, 0.0));
+                pass(&self.bloom_blur, a.texture, b, None);
+                set_uniform(&mut self.bloom_blur, "direction", Vector2::new(0.0, 1.0));
+                pass(&self.bloom_blur, b.texture, a, None);
+                set_uniform(&mut self.bloom, "intensity", s.bloom.intensity);
+                pass(&self.bloom, source, target, Some(a.texture.id));
+            }
+            Efecto::DepthOfField => {
+                set_uniform(&mut self.depth_of_field, "focusDistance", s.depth_of_field.focus_distance);
+                set_uniform(&mut self.depth_of_field, "focusRange", s.depth_of_field.focus_range);
+                set_uniform(&mut self.depth_of_field, "maxBlur", s.depth_of_field.max_blur);
+                pass(&self.depth_of_field, source, target, Some(depth));
+            }
+            Efecto::Fxaa => {
+                set_uniform(&mut self.fxaa, "spanMax", s.fxaa.span_max);
+                pass(&self.fxaa, source, target, None);
+            }
+            Efecto::ColorGrading => {
+                // Sin LUT cargada la pasada sólo copia
+                let strength = if self.lut.is_some() { s.color_grading.strength } else { 0.0 };
+                set_uniform(&mut self.color_grading, "strength", strength);
+                pass(&self.color_grading, source, target, self.lut.as_ref().map(|t| t.id));
+            }
+            Efecto::ChromaticAberration => {
+                set_uniform(&mut self.chromatic_aberration, "strength", s.chromatic_aberration.strength);
+                pass(&self.chromatic_aberration, source, target, None);
+            }
+            Efecto::Vignette => {
+                set_uniform(&mut self.vignette, "intensity", s.vignette.intensity);
+                set_uniform(&mut self.vignette, "radius", s.vignette.radius);
+                set_uniform(&mut self.vignette, "softness", s.vignette.softness);
+                pass(&self.vignette, source, target, None);
+            }
+        }
+    }
+}
+
+fn set_uniform<T: ShaderV>(shader: &mut Shader, name: &str, value: T) {
+    let loc = shader.get_shader_location(name);
+    shader.set_shader_value(loc, value);
+}
+
+// Pasada a pantalla completa: dibuja `source` con `shader` en `target`, con `extra` en EXTRA_SLOT.
+// El alto negativo corrige el flip vertical de los render textures.
+// Va por ffi porque las pasadas encadenan render textures de PostProceso (se lee uno mientras se escribe
+// en otro, y begin_texture_mode pide prestado el destino entero), y porque raylib no tiene envoltura segura
+// para atar una segunda textura a un slot (rlActiveTextureSlot / rlEnableTexture)
+fn pass(shader: &Shader, source: raylib::ffi::Texture2D, target: raylib::ffi::RenderTexture2D, extra: Option<u32>) {
+    let src = Rectangle::new(0.0, 0.0, source.width as f32, -(source.height as f32));
+    let dest = Rectangle::new(0.0, 0.0, target.texture.width as f32, target.texture.height as f32);
+    unsafe {
+        raylib::ffi::BeginTextureMode(target);
+        raylib::ffi::BeginShaderMode(*shader.as_ref());
+        if let Some(id) = extra {
+            raylib::ffi::rlActiveTextureSlot(EXTRA_SLOT);
+            raylib::ffi::rlEnableTexture(id);
+            raylib::ffi::rlActiveTextureSlot(0);
+        }
+        raylib::ffi::DrawTexturePro(source, src.into(), dest.into(), Vector2::zero().into(), 0.0, Color::WHITE.into());
+        raylib::ffi::EndShaderMode();
+        raylib::ffi::EndTextureMode();
+    }
+}
+
+// Módulo para la cadena de efectos de post-proceso
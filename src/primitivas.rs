@@ -0,0 +1,240 @@
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+use crate::malla::{Malla, Vertice};
+
+// Geometría indexada como la del cubo: vértices más triángulos con índices, en espacio local.
+// Todas las primitivas caben en [-size, size] en cada eje, igual que Cubo
+pub struct Primitiva {
+    pub positions: Vec<Vector3>,
+    pub normals: Vec<Vector3>,
+    pub uvs: Vec<Vector2>,
+    pub indices: Vec<[usize; 3]>,
+}
+
+impl Primitiva {
+    fn new() -> Self {
+        Self { positions: Vec::new(), normals: Vec::new(), uvs: Vec::new(), indices: Vec::new() }
+    }
+
+    fn push_vertex(&mut self, pos: Vector3, normal: Vector3, uv: Vector2) -> usize {
+        self.positions.push(pos);
+        self.normals.push(normal.normalized());
+        self.uvs.push(uv);
+        self.positions.len() - 1
+    }
+
+    // Quad a-b-c-d (en orden alrededor del borde) como dos triángulos
+    fn push_quad(&mut self, a: usize, b: usize, c: usize, d: usize) {
+        self.indices.push([a, b, c]);
+        self.indices.push([a, c, d]);
+    }
+
+    // Deja cada triángulo en sentido antihorario visto desde fuera, según las normales de sus vértices
+    fn orient(mut self) -> Self {
+        for tri in self.indices.iter_mut() {
+            let [a, b, c] = tri.map(|i| self.positions[i]);
+            let normal = self.normals[tri[0]] + self.normals[tri[1]] + self.normals[tri[2]];
+            if (b - a).cross(c - a).dot(normal) < 0.0 {
+                tri.swap(1, 2);
+            }
+        }
+        self
+    }
+
+    // Los triángulos degenerados (polos de la esfera, punta del cono) no se dibujan
+    pub fn into_malla(self) -> Malla {
+        let triangles = self.indices.iter()
+            .map(|t| t.map(|i| Vertice { pos: self.positions[i], normal: self.normals[i], uv: self.uvs[i] }))
+            .filter(|t| (t[1].pos - t[0].pos).cross(t[2].pos - t[0].pos).length() > 1e-9)
+            .collect();
+        Malla::new(triangles)
+    }
+}
+
+// Esfera por latitud/longitud; la costura de las UV tiene vértices duplicados
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Primitiva {
+    let (segments, rings) = (segments.max(3), rings.max(2));
+    let mut p = Primitiva::new();
+    for i in 0..=rings {
+        let theta = PI * i as f32 / rings as f32;
+        for j in 0..=segments {
+            let phi = TAU * j as f32 / segments as f32;
+            let n = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            p.push_vertex(n * radius, n, Vector2::new(j as f32 / segments as f32, i as f32 / rings as f32));
+        }
+    }
+    let row = segments as usize + 1;
+    for i in 0..rings as usize {
+        for j in 0..segments as usize {
+            let a = i * row + j;
+            p.push_quad(a, a + row, a + row + 1, a + 1);
+        }
+    }
+    p.orient()
+}
+
+// Icosaedro subdividido y proyectado a la esfera: triángulos de tamaño parecido, sin polos
+pub fn icosphere(radius: f32, subdivisions: u32) -> Primitiva {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut verts: Vec<Vector3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ].iter().map(|&(x, y, z)| Vector3::new(x, y, z).normalized()).collect();
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions.min(6) {
+        let mut midpoints = std::collections::HashMap::new();
+        let mut midpoint = |a: usize, b: usize, verts: &mut Vec<Vector3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let m = ((verts[a] + verts[b]) * 0.5).normalized();
+                verts.push(m);
+                verts.len() - 1
+            })
+        };
+        faces = faces.iter().flat_map(|&[a, b, c]| {
+            let ab = midpoint(a, b, &mut verts);
+            let bc = midpoint(b, c, &mut verts);
+            let ca = midpoint(c, a, &mut verts);
+            [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+        }).collect();
+    }
+
+    let mut p = Primitiva::new();
+    for n in verts {
+        // Proyección esférica (con la costura estirada, igual que en cualquier icosfera texturizada)
+        let uv = Vector2::new(0.5 + n.z.atan2(n.x) / TAU, n.y.clamp(-1.0, 1.0).acos() / PI);
+        p.push_vertex(n * radius, n, uv);
+    }
+    p.indices = faces;
+    p.orient()
+}
+
+// Cilindro vertical de altura 2 * half_height con tapas
+pub fn cylinder(radius: f32, half_height: f32, segments: u32) -> Primitiva {
+    let mut p = Primitiva::new();
+    push_side(&mut p, radius, radius, half_height, segments);
+    push_cap(&mut p, radius, half_height, segments, true);
+    push_cap(&mut p, radius, -half_height, segments, false);
+    p.orient()
+}
+
+// Cono vertical con la punta arriba y tapa abajo
+pub fn cone(radius: f32, half_height: f32, segments: u32) -> Primitiva {
+    let mut p = Primitiva::new();
+    push_side(&mut p, radius, 0.0, half_height, segments);
+    push_cap(&mut p, radius, -half_height, segments, false);
+    p.orient()
+}
+
+// Pared lateral entre un anillo inferior (radio `bottom`) y uno superior (radio `top`)
+fn push_side(p: &mut Primitiva, bottom: f32, top: f32, half_height: f32, segments: u32) {
+    let segments = segments.max(3);
+    // Normal inclinada según la pendiente de la pared
+    let slope = (bottom - top) / (2.0 * half_height);
+    let start = p.positions.len();
+    for j in 0..=segments {
+        let u = j as f32 / segments as f32;
+        let (s, c) = (TAU * u).sin_cos();
+        let normal = Vector3::new(c, slope, s);
+        p.push_vertex(Vector3::new(c * bottom, -half_height, s * bottom), normal, Vector2::new(u, 1.0));
+        p.push_vertex(Vector3::new(c * top, half_height, s * top), normal, Vector2::new(u, 0.0));
+    }
+    for j in 0..segments as usize {
+        let a = start + j * 2;
+        p.push_quad(a, a + 1, a + 3, a + 2);
+    }
+}
+
+// Tapa circular en y con su propio centro (normales planas)
+fn push_cap(p: &mut Primitiva, radius: f32, y: f32, segments: u32, up: bool) {
+    let segments = segments.max(3);
+    let normal = Vector3::new(0.0, if up { 1.0 } else { -1.0 }, 0.0);
+    let center = p.push_vertex(Vector3::new(0.0, y, 0.0), normal, Vector2::new(0.5, 0.5));
+    let start = p.positions.len();
+    for j in 0..=segments {
+        let (s, c) = (TAU * j as f32 / segments as f32).sin_cos();
+        p.push_vertex(Vector3::new(c * radius, y, s * radius), normal, Vector2::new(0.5 + c * 0.5, 0.5 + s * 0.5));
+    }
+    for j in 0..segments as usize {
+        p.indices.push([center, start + j, start + j + 1]);
+    }
+}
+
+// Toro acostado en el plano XZ
+pub fn torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> Primitiva {
+    let (segments, sides) = (segments.max(3), sides.max(3));
+    let mut p = Primitiva::new();
+    for i in 0..=segments {
+        let u = i as f32 / segments as f32;
+        let (su, cu) = (TAU * u).sin_cos();
+        for j in 0..=sides {
+            let v = j as f32 / sides as f32;
+            let (sv, cv) = (TAU * v).sin_cos();
+            let normal = Vector3::new(cu * cv, sv, su * cv);
+            let pos = Vector3::new(cu * major_radius, 0.0, su * major_radius) + normal * minor_radius;
+            p.push_vertex(pos, normal, Vector2::new(u, v));
+        }
+    }
+    let row = sides as usize + 1;
+    for i in 0..segments as usize {
+        for j in 0..sides as usize {
+            let a = i * row + j;
+            p.push_quad(a, a + row, a + row + 1, a + 1);
+        }
+    }
+    p.orient()
+}
+
+// Plano horizontal de 2 * size de lado, dividido en subdivisions x subdivisions celdas
+pub fn plane(size: f32, subdivisions: u32) -> Primitiva {
+    let mut p = Primitiva::new();
+    push_grid(&mut p, Vector3::new(0.0, 0.0, 0.0), Vector3::new(size, 0.0, 0.0), Vector3::new(0.0, 0.0, size),
+              Vector3::new(0.0, 1.0, 0.0), subdivisions);
+    p.orient()
+}
+
+// Cubo con cada cara dividida en una cuadrícula (mejor iluminación por vértice que el de 12 triángulos)
+pub fn subdivided_cube(size: f32, subdivisions: u32) -> Primitiva {
+    let mut p = Primitiva::new();
+    let axes = [
+        Vector3::new(1.0, 0.0, 0.0), Vector3::new(-1.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, -1.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, -1.0),
+    ];
+    for normal in axes {
+        // Base de la cara: u a lo largo del plano y v hacia "arriba" (o hacia z en las tapas)
+        let up = if normal.y.abs() > 0.5 { Vector3::new(0.0, 0.0, 1.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+        let u = up.cross(normal);
+        push_grid(&mut p, normal * size, u * size, up * size, normal, subdivisions);
+    }
+    p.orient()
+}
+
+// Cuadrícula centrada en `center` que se extiende ±u y ±v
+fn push_grid(p: &mut Primitiva, center: Vector3, u: Vector3, v: Vector3, normal: Vector3, subdivisions: u32) {
+    let n = subdivisions.max(1);
+    let start = p.positions.len();
+    for i in 0..=n {
+        let tv = i as f32 / n as f32;
+        for j in 0..=n {
+            let tu = j as f32 / n as f32;
+            let pos = center + u * (tu * 2.0 - 1.0) + v * (1.0 - tv * 2.0);
+            p.push_vertex(pos, normal, Vector2::new(tu, tv));
+        }
+    }
+    let row = n as usize + 1;
+    for i in 0..n as usize {
+        for j in 0..n as usize {
+            let a = start + i * row + j;
+            p.push_quad(a, a + row, a + row + 1, a + 1);
+        }
+    }
+}
+
+// Módulo para primitivas procedurales
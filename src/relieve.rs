@@ -0,0 +1,61 @@
+use raylib::prelude::*;
+
+// Capas de la búsqueda de parallax: más cuanto más rasante se mira la superficie
+const PARALLAX_MIN_LAYERS: f32 = 8.0;
+const PARALLAX_MAX_LAYERS: f32 = 32.0;
+
+// Base tangente de un triángulo calculada con sus UV: (tangente = dP/du, bitangente = dP/dv),
+// ortogonalizadas contra la normal. Es la misma base que arma cotangentFrame en los shaders
+pub fn tangent_frame(v: [Vector3; 3], uv: [Vector2; 3], normal: Vector3) -> (Vector3, Vector3) {
+    let (e1, e2) = (v[1] - v[0], v[2] - v[0]);
+    let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+    let det = d1.x * d2.y - d2.x * d1.y;
+    if det.abs() < 1e-12 {
+        // Sin UV útiles (p. ej. el piso): cualquier base perpendicular a la normal
+        let helper = if normal.x.abs() > 0.9 { Vector3::new(0.0, 1.0, 0.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+        let tangent = helper.cross(normal).normalized();
+        return (tangent, normal.cross(tangent));
+    }
+    let tangent = (e1 * d2.y - e2 * d1.y) / det;
+    let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+    (
+        (tangent - normal * normal.dot(tangent)).normalized(),
+        (bitangent - normal * normal.dot(bitangent)).normalized(),
+    )
+}
+
+// Texel de un mapa de normales (0..1) a espacio mundo. Convención OpenGL: el verde apunta
+// hacia arriba en la imagen, y como v crece hacia abajo eso es -bitangente
+pub fn perturb_normal(texel: Vector3, tangent: Vector3, bitangent: Vector3, normal: Vector3, strength: f32) -> Vector3 {
+    let n = texel * 2.0 - Vector3::one();
+    (tangent * (n.x * strength) - bitangent * (n.y * strength) + normal * n.z).normalized()
+}
+
+// Parallax occlusion mapping: recorre la altura (1 = superficie, 0 = lo más hondo) a lo largo
+// de la dirección de vista en espacio tangente y devuelve la UV que realmente se ve
+pub fn parallax_uv(uv: Vector2, view_ts: Vector3, scale: f32, height: impl Fn(Vector2) -> f32) -> Vector2 {
+    if view_ts.z <= 1e-4 {
+        return uv;
+    }
+    let layers = PARALLAX_MAX_LAYERS + (PARALLAX_MIN_LAYERS - PARALLAX_MAX_LAYERS) * view_ts.z.min(1.0);
+    let layer_depth = 1.0 / layers;
+    let delta = Vector2::new(view_ts.x, view_ts.y) / view_ts.z * scale / layers;
+
+    let mut current_uv = uv;
+    let mut current_depth = 0.0;
+    let mut map_depth = 1.0 - height(current_uv);
+    while current_depth < map_depth && current_depth < 1.0 {
+        current_uv = current_uv - delta;
+        map_depth = 1.0 - height(current_uv);
+        current_depth += layer_depth;
+    }
+
+    // Interpolar entre la última capa sobre la superficie y la primera por debajo
+    let previous_uv = current_uv + delta;
+    let after = map_depth - current_depth;
+    let before = (1.0 - height(previous_uv)) - (current_depth - layer_depth);
+    let weight = if (after - before).abs() > 1e-6 { after / (after - before) } else { 0.0 };
+    previous_uv * weight + current_uv * (1.0 - weight)
+}
+
+// Módulo para mapas de normales y de altura
@@ -0,0 +1,253 @@
+use raylib::prelude::*;
+use crate::{entorno::Skybox, light::{self, Light, MAX_LIGHTS}, material::Material, sombras::ShadowMaps};
+
+pub const LIGHTING_VS: &str = "resources/shaders/lighting.vs";
+const LIGHTING_FS: &str = "resources/shaders/lighting.fs";
+const PBR_FS: &str = "resources/shaders/pbr.fs";
+const VERTEX_HDR_FS: &str = "resources/shaders/vertex_hdr.fs";
+
+// Primera unidad de textura de los shadow maps, uno por luz (la 0 es la textura del objeto)
+const SHADOW_MAP_SLOT: i32 = 10;
+// Mapa de entorno, justo antes de los shadow maps
+const ENVIRONMENT_SLOT: i32 = SHADOW_MAP_SLOT - 1;
+// Mapas de normales y de altura del objeto, después de los shadow maps
+const NORMAL_MAP_SLOT: i32 = SHADOW_MAP_SLOT + MAX_LIGHTS as i32;
+const HEIGHT_MAP_SLOT: i32 = NORMAL_MAP_SLOT + 1;
+
+// Ubicaciones de los uniforms de una luz (índice i de los arrays de lighting.fs)
+struct LightLocs {
+    kind: i32,
+    pos: i32,
+    dir: i32,
+    color: i32,
+    atten: i32,
+    cone: i32,
+    vp: i32,
+    shadow_map: i32,
+}
+
+// Shader de iluminación por fragmento: Phong (lighting.fs) o PBR (pbr.fs), ambos con lighting.vs.
+// Los uniforms que un shader no usa tienen ubicación -1 y se ignoran
+pub struct LightingShader {
+    shader: Shader,
+    lights: Vec<LightLocs>,
+    light_count_loc: i32,
+    view_pos_loc: i32,
+    object_color_loc: i32,
+    ambient_loc: i32,
+    diffuse_loc: i32,
+    specular_loc: i32,
+    shininess_loc: i32,
+    emissive_loc: i32,
+    metallic_loc: i32,
+    roughness_loc: i32,
+    normal_map_loc: i32,
+    height_map_loc: i32,
+    use_normal_map_loc: i32,
+    use_height_map_loc: i32,
+    normal_strength_loc: i32,
+    parallax_scale_loc: i32,
+    model_loc: i32,
+    shadow_count_loc: i32,
+    shadow_resolution_loc: i32,
+    pcf_radius_loc: i32,
+    shadow_depth_ids: Vec<u32>,
+    normal_map_id: Option<u32>,
+    height_map_id: Option<u32>,
+    environment_id: Option<u32>,
+}
+
+impl LightingShader {
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        Self::load_fragment(rl, thread, LIGHTING_FS)
+    }
+
+    // Cook-Torrance GGX con metallic / roughness
+    pub fn load_pbr(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        Self::load_fragment(rl, thread, PBR_FS)
+    }
+
+    fn load_fragment(rl: &mut RaylibHandle, thread: &RaylibThread, fragment: &str) -> Self {
+        let shader = rl.load_shader(thread, Some(LIGHTING_VS), Some(fragment));
+
+        let lights = (0..MAX_LIGHTS)
+            .map(|i| LightLocs {
+                kind: shader.get_shader_location(&format!("lightType[{}]", i)),
+                pos: shader.get_shader_location(&format!("lightPos[{}]", i)),
+                dir: shader.get_shader_location(&format!("lightDir[{}]", i)),
+                color: shader.get_shader_location(&format!("lightColor[{}]", i)),
+                atten: shader.get_shader_location(&format!("lightAtten[{}]", i)),
+                cone: shader.get_shader_location(&format!("lightCone[{}]", i)),
+                vp: shader.get_shader_location(&format!("lightVP[{}]", i)),
+                shadow_map: shader.get_shader_location(&format!("shadowMap{}", i)),
+            })
+            .collect();
+
+        Self {
+            lights,
+            light_count_loc: shader.get_shader_location("lightCount"),
+            view_pos_loc: shader.get_shader_location("viewPos"),
+            object_color_loc: shader.get_shader_location("objectColor"),
+            ambient_loc: shader.get_shader_location("ambientStrength"),
+            diffuse_loc: shader.get_shader_location("diffuseStrength"),
+            specular_loc: shader.get_shader_location("specularStrength"),
+            shininess_loc: shader.get_shader_location("shininess"),
+            emissive_loc: shader.get_shader_location("emissiveColor"),
+            metallic_loc: shader.get_shader_location("metallic"),
+            roughness_loc: shader.get_shader_location("roughness"),
+            normal_map_loc: shader.get_shader_location("normalMap"),
+            height_map_loc: shader.get_shader_location("heightMap"),
+            use_normal_map_loc: shader.get_shader_location("useNormalMap"),
+            use_height_map_loc: shader.get_shader_location("useHeightMap"),
+            normal_strength_loc: shader.get_shader_location("normalStrength"),
+            parallax_scale_loc: shader.get_shader_location("parallaxScale"),
+            model_loc: shader.get_shader_location("matModel"),
+            shadow_count_loc: shader.get_shader_location("shadowCount"),
+            shadow_resolution_loc: shader.get_shader_location("shadowMapResolution"),
+            pcf_radius_loc: shader.get_shader_location("pcfRadius"),
+            shadow_depth_ids: Vec::new(),
+            normal_map_id: None,
+            height_map_id: None,
+            environment_id: None,
+            shader,
+        }
+    }
+
+    // Si los archivos no compilan raylib devuelve el shader por defecto
+    pub fn is_ready(&self) -> bool {
+        self.shader.id != unsafe { raylib::ffi::rlGetShaderIdDefault() }
+    }
+
+    // Actualiza los uniforms con el estado de las luces y la cámara (una vez por frame)
+    pub fn update(&mut self, lights: &[Light], camera_pos: Vector3) {
+        let count = lights.len().min(MAX_LIGHTS);
+        self.shader.set_shader_value(self.light_count_loc, count as i32);
+        for (light, locs) in lights.iter().zip(&self.lights) {
+            let cone = Vector2::new(light.inner_angle.to_radians().cos(), light.outer_angle.to_radians().cos());
+            self.shader.set_shader_value(locs.kind, light.kind.shader_id());
+            self.shader.set_shader_value(locs.pos, light.position_vec3());
+            self.shader.set_shader_value(locs.dir, light.direction_vec3());
+            self.shader.set_shader_value(locs.color, light::color_vec(light));
+            self.shader.set_shader_value(locs.atten, Vector3::new(light.attenuation[0], light.attenuation[1], light.attenuation[2]));
+            self.shader.set_shader_value(locs.cone, cone);
+        }
+        self.shader.set_shader_value(self.view_pos_loc, camera_pos);
+        // Los objetos envían sus vértices ya transformados a espacio mundo
+        self.shader.set_shader_value_matrix(self.model_loc, Matrix::identity());
+    }
+
+    // Material del objeto que se va a dibujar; llamar antes de begin().
+    // `textures` son las de la escena, de ahí salen los mapas de normales y de altura
+    pub fn set_material(&mut self, material: &Material, textures: &[Texture2D]) {
+        // El albedo se multiplica por la textura y el color de vértice
+        self.shader.set_shader_value(self.object_color_loc, material.albedo);
+        self.shader.set_shader_value(self.ambient_loc, material.ambient);
+        self.shader.set_shader_value(self.diffuse_loc, material.diffuse);
+        self.shader.set_shader_value(self.specular_loc, material.specular);
+        self.shader.set_shader_value(self.shininess_loc, material.shininess);
+        self.shader.set_shader_value(self.emissive_loc, material.emissive);
+        self.shader.set_shader_value(self.metallic_loc, material.metallic);
+        self.shader.set_shader_value(self.roughness_loc, material.roughness);
+
+        self.normal_map_id = material.normal_map.and_then(|i| textures.get(i)).map(|t| t.id);
+        self.height_map_id = material.height_map.and_then(|i| textures.get(i)).map(|t| t.id);
+        self.shader.set_shader_value(self.normal_map_loc, NORMAL_MAP_SLOT);
+        self.shader.set_shader_value(self.height_map_loc, HEIGHT_MAP_SLOT);
+        self.shader.set_shader_value(self.use_normal_map_loc, self.normal_map_id.is_some() as i32);
+        self.shader.set_shader_value(self.use_height_map_loc, self.height_map_id.is_some() as i32);
+        self.shader.set_shader_value(self.normal_strength_loc, material.normal_strength);
+        self.shader.set_shader_value(self.parallax_scale_loc, material.parallax_scale);
+    }
+
+    // Conecta los shadow maps de este frame (None desactiva las sombras)
+    pub fn update_shadows(&mut self, shadow_maps: Option<&ShadowMaps>) {
+        let count = shadow_maps.map_or(0, |sm| sm.count().min(MAX_LIGHTS));
+        self.shadow_depth_ids = shadow_maps.map_or(Vec::new(), |sm| (0..count).map(|i| sm.depth_texture_id(i)).collect());
+        self.shader.set_shader_value(self.shadow_count_loc, count as i32);
+        if let Some(sm) = shadow_maps {
+            self.shader.set_shader_value(self.shadow_resolution_loc, sm.settings.resolution);
+            self.shader.set_shader_value(self.pcf_radius_loc, sm.settings.pcf_radius);
+            for (i, locs) in self.lights.iter().enumerate().take(count) {
+                self.shader.set_shader_value_matrix(locs.vp, sm.light_vp(i));
+                self.shader.set_shader_value(locs.shadow_map, SHADOW_MAP_SLOT + i as i32);
+            }
+        }
+    }
+
+    // Entorno para la iluminación basada en imagen (una vez, al cargar la escena); None o sin `ibl` la desactiva
+    pub fn set_environment(&mut self, skybox: Option<&Skybox>) {
+        let skybox = skybox.filter(|s| s.ibl);
+        self.environment_id = skybox.map(Skybox::texture_id);
+        let use_loc = self.shader.get_shader_location("useEnvironment");
+        self.shader.set_shader_value(use_loc, skybox.is_some() as i32);
+        if let Some(skybox) = skybox {
+            let map_loc = self.shader.get_shader_location("environmentMap");
+            let intensity_loc = self.shader.get_shader_location("environmentIntensity");
+            let rotation_loc = self.shader.get_shader_location("environmentRotation");
+            let max_lod_loc = self.shader.get_shader_location("environmentMaxLod");
+            self.shader.set_shader_value(map_loc, ENVIRONMENT_SLOT);
+            self.shader.set_shader_value(intensity_loc, skybox.intensity);
+            self.shader.set_shader_value(rotation_loc, skybox.rotation);
+            self.shader.set_shader_value(max_lod_loc, skybox.max_lod);
+            for (i, coefficient) in skybox.sh.iter().enumerate() {
+                let loc = self.shader.get_shader_location(&format!("environmentSH[{}]", i));
+                self.shader.set_shader_value(loc, *coefficient);
+            }
+        }
+    }
+
+    // Activa el shader y enlaza los shadow maps, el entorno y los mapas del material; cerrar siempre con end()
+    pub fn begin(&self) {
+        unsafe {
+            raylib::ffi::BeginShaderMode(*self.shader.as_ref());
+            for (i, &depth_id) in self.shadow_depth_ids.iter().enumerate() {
+                raylib::ffi::rlActiveTextureSlot(SHADOW_MAP_SLOT + i as i32);
+                raylib::ffi::rlEnableTexture(depth_id);
+            }
+            let maps = [
+                (ENVIRONMENT_SLOT, self.environment_id),
+                (NORMAL_MAP_SLOT, self.normal_map_id),
+                (HEIGHT_MAP_SLOT, self.height_map_id),
+            ];
+            for (slot, id) in maps {
+                if let Some(id) = id {
+                    raylib::ffi::rlActiveTextureSlot(slot);
+                    raylib::ffi::rlEnableTexture(id);
+                }
+            }
+            raylib::ffi::rlActiveTextureSlot(0);
+        }
+    }
+
+    pub fn end(&self) {
+        unsafe {
+            raylib::ffi::EndShaderMode();
+        }
+    }
+}
+
+// Modos por CPU: expande la luz que llega comprimida en el color de vértice (tonos::encode_vertex_color)
+// y pasa la textura a lineal, para que el framebuffer HDR reciba lo mismo que desde lighting.fs
+pub struct VertexColorShader {
+    shader: Shader,
+}
+
+impl VertexColorShader {
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        Self { shader: rl.load_shader(thread, None, Some(VERTEX_HDR_FS)) }
+    }
+
+    pub fn begin(&self) {
+        unsafe {
+            raylib::ffi::BeginShaderMode(*self.shader.as_ref());
+        }
+    }
+
+    pub fn end(&self) {
+        unsafe {
+            raylib::ffi::EndShaderMode();
+        }
+    }
+}
+
+// Módulo para shaders
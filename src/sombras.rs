@@ -0,0 +1,174 @@
+use raylib::prelude::*;
+use crate::{escena::Escena, framebuffer, light::{Light, LightKind, MAX_LIGHTS}};
+
+// Proyección usada para renderizar la profundidad desde la luz
+const LIGHT_FOVY: f32 = 90.0;
+const LIGHT_NEAR: f32 = 0.1;
+const LIGHT_FAR: f32 = 40.0;
+
+// Caja ortográfica de las luces direccionales (centrada en el foco de la escena)
+const DIRECTIONAL_HALF_SIZE: f32 = 8.0;
+const DIRECTIONAL_DISTANCE: f32 = 20.0;
+
+// Límites de la configuración (la escena se valida con ellos; en la ventana se cambian dentro de ellos)
+pub const MIN_RESOLUTION: i32 = 256;
+pub const MAX_RESOLUTION: i32 = 8192;
+pub const MAX_PCF_RADIUS: i32 = 4;
+
+// Configuración del shadow map
+#[derive(Clone, Copy)]
+pub struct ShadowSettings {
+    pub resolution: i32, // Ancho y alto del mapa de profundidad en píxeles
+    pub pcf_radius: i32, // Radio del filtro PCF en texels (0 = sombras duras)
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 2048,
+            pcf_radius: 1,
+        }
+    }
+}
+
+// Mapa de profundidad renderizado desde una luz
+struct ShadowMap {
+    target: RenderTexture2D,
+    light_vp: Matrix,
+}
+
+// Un shadow map por luz de la escena (como máximo MAX_LIGHTS)
+pub struct ShadowMaps {
+    pub settings: ShadowSettings,
+    maps: Vec<ShadowMap>,
+}
+
+impl ShadowMaps {
+    pub fn new(settings: ShadowSettings, light_count: usize) -> Self {
+        let maps = (0..light_count.min(MAX_LIGHTS))
+            .map(|_| ShadowMap {
+                target: load_depth_target(settings.resolution),
+                light_vp: Matrix::identity(),
+            })
+            .collect();
+        Self { settings, maps }
+    }
+
+    pub fn count(&self) -> usize {
+        self.maps.len()
+    }
+
+    pub fn depth_texture_id(&self, i: usize) -> u32 {
+        self.maps[i].target.depth.id
+    }
+
+    pub fn light_vp(&self, i: usize) -> Matrix {
+        self.maps[i].light_vp
+    }
+
+    // Pasada de profundidad: renderiza la geometría de la escena vista desde cada luz
+    pub fn render(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread, escena: &Escena) {
+        // Si cambió la resolución se vuelven a crear los mapas de profundidad (el anterior se libera al soltarlo)
+        let resolution = self.settings.resolution;
+        for map in self.maps.iter_mut().filter(|m| m.target.depth.width != resolution) {
+            map.target = load_depth_target(resolution);
+        }
+        for (map, light) in self.maps.iter_mut().zip(&escena.lights) {
+            map.render(d, thread, escena, light);
+        }
+    }
+}
+
+impl ShadowMap {
+    fn render(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread, escena: &Escena, light: &Light) {
+        let (view, proj) = light_view_projection(light, escena.shadow_focus());
+        self.light_vp = view * proj;
+
+        let mut dt = d.begin_texture_mode(thread, &mut self.target);
+        dt.clear_background(Color::WHITE);
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            raylib::ffi::rlSetMatrixProjection(proj.into());
+            raylib::ffi::rlSetMatrixModelview(view.into());
+            raylib::ffi::rlEnableDepthTest();
+            raylib::ffi::rlDisableBackfaceCulling();
+        }
+        escena.render_depth();
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            raylib::ffi::rlEnableBackfaceCulling();
+        }
+        // EndTextureMode restaura viewport y matrices
+    }
+}
+
+// Vista y proyección desde la luz según su tipo
+fn light_view_projection(light: &Light, focus: Vector3) -> (Matrix, Matrix) {
+    match light.kind {
+        // Ortográfica centrada en el foco, con el "ojo" retrocedido en contra de la dirección
+        LightKind::Directional => {
+            let dir = light.direction_vec3();
+            let eye = focus - dir * DIRECTIONAL_DISTANCE;
+            let h = DIRECTIONAL_HALF_SIZE;
+            let view = Matrix::look_at(eye, focus, up_for(dir));
+            let proj = Matrix::ortho(-h, h, -h, h, LIGHT_NEAR, LIGHT_FAR);
+            (view, proj)
+        }
+        // Perspectiva a lo largo del eje del foco, cubriendo el cono exterior
+        LightKind::Spot => {
+            let dir = light.direction_vec3();
+            let pos = light.position_vec3();
+            let fovy = (2.0 * light.outer_angle).clamp(1.0, 170.0);
+            let view = Matrix::look_at(pos, pos + dir, up_for(dir));
+            let proj = Matrix::perspective(fovy.to_radians(), 1.0, LIGHT_NEAR, LIGHT_FAR);
+            (view, proj)
+        }
+        // Puntual: perspectiva mirando al centro de la escena
+        LightKind::Point => {
+            let pos = light.position_vec3();
+            let dir = (focus - pos).normalized();
+            let view = Matrix::look_at(pos, focus, up_for(dir));
+            let proj = Matrix::perspective(LIGHT_FOVY.to_radians(), 1.0, LIGHT_NEAR, LIGHT_FAR);
+            (view, proj)
+        }
+    }
+}
+
+// Evitar un vector "up" paralelo a la dirección de la luz
+fn up_for(dir: Vector3) -> Vector3 {
+    if dir.y.abs() > 0.99 { Vector3::new(0.0, 0.0, 1.0) } else { Vector3::new(0.0, 1.0, 0.0) }
+}
+
+// Framebuffer sólo con textura de profundidad (sin color)
+fn load_depth_target(resolution: i32) -> RenderTexture2D {
+    unsafe {
+        let mut target: raylib::ffi::RenderTexture2D = std::mem::zeroed();
+        target.id = raylib::ffi::rlLoadFramebuffer();
+        target.texture.width = resolution;
+        target.texture.height = resolution;
+
+        if target.id > 0 {
+            raylib::ffi::rlEnableFramebuffer(target.id);
+            target.depth.id = raylib::ffi::rlLoadTextureDepth(resolution, resolution, false);
+            target.depth.width = resolution;
+            target.depth.height = resolution;
+            target.depth.format = framebuffer::DEPTH_FORMAT as i32;
+            target.depth.mipmaps = 1;
+            raylib::ffi::rlFramebufferAttach(
+                target.id,
+                target.depth.id,
+                framebuffer::ATTACH_DEPTH as i32,
+                framebuffer::ATTACH_TEXTURE2D as i32,
+                0,
+            );
+            if !raylib::ffi::rlFramebufferComplete(target.id) {
+                eprintln!("Shadow map incompleto, las sombras pueden no verse");
+            }
+            raylib::ffi::rlDisableFramebuffer();
+        }
+
+        RenderTexture2D::from_raw(target)
+    }
+}
+
+// Módulo para sombras con shadow mapping
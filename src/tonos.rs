@@ -0,0 +1,107 @@
+use raylib::prelude::*;
+use crate::pbr;
+
+// Blanco de la curva filmic: la luz que termina en 1.0
+const FILMIC_WHITE: f32 = 11.2;
+// Los colores de vértice se guardan comprimidos hasta este valor (ver encode_vertex_color)
+const VERTEX_ENCODE_MAX: f32 = 254.5 / 255.0;
+
+// Operador de tono: lleva la luz lineal (sin límite) a 0..1 antes de pasarla a sRGB.
+// Mismas curvas que resources/shaders/tonemap.fs
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tonemapper {
+    Linear,   // Sólo recorta en 1.0
+    Reinhard, // c / (1 + c)
+    Aces,     // Ajuste de Narkowicz a la curva ACES
+    Filmic,   // Curva de Hable (Uncharted 2)
+}
+
+impl Tonemapper {
+    pub fn next(self) -> Self {
+        match self {
+            Tonemapper::Linear => Tonemapper::Reinhard,
+            Tonemapper::Reinhard => Tonemapper::Aces,
+            Tonemapper::Aces => Tonemapper::Filmic,
+            Tonemapper::Filmic => Tonemapper::Linear,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tonemapper::Linear => "Lineal",
+            Tonemapper::Reinhard => "Reinhard",
+            Tonemapper::Aces => "ACES",
+            Tonemapper::Filmic => "Filmic",
+        }
+    }
+
+    // Valor del uniform `tonemapper` en tonemap.fs
+    pub fn shader_id(self) -> i32 {
+        match self {
+            Tonemapper::Linear => 0,
+            Tonemapper::Reinhard => 1,
+            Tonemapper::Aces => 2,
+            Tonemapper::Filmic => 3,
+        }
+    }
+
+    pub fn apply(self, c: f32) -> f32 {
+        let c = c.max(0.0);
+        let mapped = match self {
+            Tonemapper::Linear => c,
+            Tonemapper::Reinhard => c / (1.0 + c),
+            Tonemapper::Aces => (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14),
+            Tonemapper::Filmic => hable(2.0 * c) / hable(FILMIC_WHITE),
+        };
+        mapped.clamp(0.0, 1.0)
+    }
+}
+
+fn hable(x: f32) -> f32 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+}
+
+// Exposición y operador de tono de la imagen final (ventana y trazador sin ventana)
+#[derive(Clone, Copy)]
+pub struct Tonemap {
+    pub tonemapper: Tonemapper,
+    pub exposure: f32, // En pasos (EV): +1 duplica la luz, -1 la divide a la mitad
+}
+
+impl Default for Tonemap {
+    fn default() -> Self {
+        Self { tonemapper: Tonemapper::Aces, exposure: 0.0 }
+    }
+}
+
+impl Tonemap {
+    // Multiplicador de la luz que corresponde a `exposure`
+    pub fn exposure_scale(&self) -> f32 {
+        2.0_f32.powf(self.exposure)
+    }
+
+    // Luz lineal -> un canal sRGB de 8 bits, igual que la pasada final de la ventana
+    pub fn to_srgb8(&self, c: f32) -> u8 {
+        let mapped = self.tonemapper.apply(c * self.exposure_scale());
+        (pbr::linear_to_srgb(mapped) * 255.0).round() as u8
+    }
+}
+
+// rlgl guarda los colores de vértice en 8 bits: la luz calculada en CPU se comprime con c / (1 + c)
+// y resources/shaders/vertex_hdr.fs la expande en el framebuffer HDR, así no se recorta en 1.0
+pub fn encode_vertex_color(color: Vector3) -> Color {
+    let encode = |c: f32| {
+        let c = c.max(0.0);
+        ((c / (1.0 + c)).min(VERTEX_ENCODE_MAX) * 255.0).round() as u8
+    };
+    Color::new(encode(color.x), encode(color.y), encode(color.z), 255)
+}
+
+// Color sRGB de 8 bits (como `background`) pasado a lineal para dibujar en el framebuffer HDR
+pub fn srgb_to_linear_color(color: Color) -> Color {
+    let linear = |c: u8| (pbr::srgb_to_linear(c as f32 / 255.0) * 255.0).round() as u8;
+    Color::new(linear(color.r), linear(color.g), linear(color.b), color.a)
+}
+
+// Módulo para exposición y mapeo de tonos (HDR -> pantalla)
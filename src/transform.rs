@@ -0,0 +1,62 @@
+use raylib::prelude::*;
+
+// Transformación de un objeto: escala, luego rotación y luego traslación
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub position: Vector3,
+    pub rotation: Quaternion, // Cuaternión unitario (x, y, z, w)
+    pub scale: Vector3,
+}
+
+impl Transform {
+    pub fn new(position: Vector3, rotation: Quaternion, scale: Vector3) -> Self {
+        Self { position, rotation, scale }
+    }
+
+    // Ángulos de Euler en grados, aplicados en orden X, Y, Z
+    pub fn from_euler_degrees(position: Vector3, euler: Vector3, scale: Vector3) -> Self {
+        let qx = axis_angle(Vector3::new(1.0, 0.0, 0.0), euler.x.to_radians());
+        let qy = axis_angle(Vector3::new(0.0, 1.0, 0.0), euler.y.to_radians());
+        let qz = axis_angle(Vector3::new(0.0, 0.0, 1.0), euler.z.to_radians());
+        Self::new(position, quat_mul(qz, quat_mul(qy, qx)), scale)
+    }
+
+    // Punto en espacio local -> espacio mundo
+    pub fn transform_point(&self, p: Vector3) -> Vector3 {
+        self.position + self.rotate(p * self.scale)
+    }
+
+    // Normal en espacio local -> espacio mundo (inversa transpuesta con escala no uniforme)
+    pub fn transform_normal(&self, n: Vector3) -> Vector3 {
+        self.rotate(n / self.scale).normalized()
+    }
+
+    pub fn rotate(&self, v: Vector3) -> Vector3 {
+        let q = Vector3::new(self.rotation.x, self.rotation.y, self.rotation.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.rotation.w + q.cross(t)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(Vector3::zero(), Quaternion::new(0.0, 0.0, 0.0, 1.0), Vector3::one())
+    }
+}
+
+fn axis_angle(axis: Vector3, angle: f32) -> Quaternion {
+    let (s, c) = (angle * 0.5).sin_cos();
+    Quaternion::new(axis.x * s, axis.y * s, axis.z * s, c)
+}
+
+// Producto de Hamilton a * b (aplica b primero y luego a)
+fn quat_mul(a: Quaternion, b: Quaternion) -> Quaternion {
+    Quaternion::new(
+        a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+        a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+        a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+    )
+}
+
+// Módulo para transformaciones
@@ -0,0 +1,310 @@
+use raylib::prelude::*;
+use image::{Rgb, RgbImage, RgbaImage};
+use std::f32::consts::PI;
+use crate::{entorno::Entorno, escena::Escena, light::{self, Light}, material::Material, pbr, relieve};
+use crate::intersecto_ray::{self, Ray};
+use crate::malla::TrianguloUV;
+
+// Por encima de esta rugosidad el reflejo del entorno sale del mip prefiltrado en vez de trazarse
+const TRACED_REFLECTION_ROUGHNESS: f32 = 0.25;
+
+// Parámetros del trazador por CPU (no necesita ventana ni GPU)
+pub struct TraceSettings {
+    pub width: u32,
+    pub height: u32,
+    pub samples_per_pixel: u32,
+    pub max_bounces: u32,
+}
+
+impl Default for TraceSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            samples_per_pixel: 16,
+            max_bounces: 2,
+        }
+    }
+}
+
+// Triángulo de la escena en espacio mundo con lo necesario para sombrearlo
+struct Triangulo {
+    v: [Vector3; 3],
+    uv: [Vector2; 3],
+    normal: Vector3,          // Normal geométrica de la cara (de qué lado se ve)
+    normals: [Vector3; 3],    // Normales de los vértices, se interpolan para sombrear
+    tangent: Vector3, // Base tangente para los mapas de normales y de altura (relieve::tangent_frame)
+    bitangent: Vector3,
+    material: Material,
+}
+
+struct Trazador<'a> {
+    triangles: Vec<Triangulo>,
+    shadow_triangles: Vec<(Vector3, Vector3, Vector3)>,
+    lights: &'a [Light],
+    background: Vector3,
+    entorno: Option<&'a Entorno>,
+    textures: &'a [RgbaImage],
+    max_bounces: u32,
+}
+
+// Renderiza la escena vista desde `cam` y devuelve la imagen resultante.
+// `textures` sigue el mismo orden que Escena::textures (SceneFile::texture_paths).
+pub fn render(escena: &Escena, cam: &Camera3D, textures: &[RgbaImage], settings: &TraceSettings) -> RgbImage {
+    let triangles = collect_triangles(escena);
+    let shadow_triangles = triangles.iter().map(|t| (t.v[0], t.v[1], t.v[2])).collect();
+    let bg = escena.background_color;
+    let trazador = Trazador {
+        triangles,
+        shadow_triangles,
+        lights: &escena.lights,
+        // El fondo está en sRGB; en lineal para que salga igual en la imagen final
+        background: Vector3::new(
+            pbr::srgb_to_linear(bg.r as f32 / 255.0),
+            pbr::srgb_to_linear(bg.g as f32 / 255.0),
+            pbr::srgb_to_linear(bg.b as f32 / 255.0),
+        ),
+        entorno: escena.entorno.as_ref(),
+        textures,
+        max_bounces: settings.max_bounces,
+    };
+
+    let spp = settings.samples_per_pixel.max(1);
+    let mut img = RgbImage::new(settings.width, settings.height);
+    // Semilla fija para que las imágenes de referencia sean reproducibles
+    let mut rng = Rng::new(0x9E37_79B9);
+
+    for y in 0..settings.height {
+        for x in 0..settings.width {
+            let mut acc = Vector3::zero();
+            for _ in 0..spp {
+                let px = x as f32 + rng.next_f32();
+                let py = y as f32 + rng.next_f32();
+                let ray = camera_ray(cam, px, py, settings.width, settings.height);
+                acc = acc + trazador.radiance(&ray, 0, &mut rng);
+            }
+            // Misma exposición y operador de tono que la ventana
+            let c = acc / spp as f32;
+            let tonemap = &escena.tonemap;
+            img.put_pixel(x, y, Rgb([tonemap.to_srgb8(c.x), tonemap.to_srgb8(c.y), tonemap.to_srgb8(c.z)]));
+        }
+    }
+    img
+}
+
+// Renderiza y guarda directamente en un archivo (el formato sale de la extensión, p. ej. PNG)
+pub fn render_to_file(escena: &Escena, cam: &Camera3D, textures: &[RgbaImage],
+                      settings: &TraceSettings, path: &str) -> image::ImageResult<()> {
+    render(escena, cam, textures, settings).save(path)
+}
+
+impl Trazador<'_> {
+    fn intersect(&self, ray: &Ray) -> Option<(f32, &Triangulo, f32, f32)> {
+        let mut closest: Option<(f32, &Triangulo, f32, f32)> = None;
+        for tri in &self.triangles {
+            if let Some((t, u, v)) = intersecto_ray::ray_triangle_intersect_uv(ray, tri.v[0], tri.v[1], tri.v[2]) {
+                if closest.is_none_or(|c| t < c.0) {
+                    closest = Some((t, tri, u, v));
+                }
+            }
+        }
+        closest
+    }
+
+    fn radiance(&self, ray: &Ray, depth: u32, rng: &mut Rng) -> Vector3 {
+        let Some((t, tri, u, v)) = self.intersect(ray) else {
+            return self.miss(ray.direction, depth);
+        };
+
+        let point = ray.origin + ray.direction * t;
+        let view_dir = -ray.direction;
+        let (uv, shading_normal) = self.surface(tri, u, v, view_dir);
+        // Las caras se ven por ambos lados, igual que en el render en tiempo real
+        let normal = if tri.normal.dot(ray.direction) > 0.0 { -shading_normal } else { shading_normal };
+        let albedo = self.albedo(tri, uv);
+        let material = &tri.material;
+
+        let diffuse_albedo = albedo * (1.0 - material.metallic);
+        let mut color = material.emissive;
+        match self.entorno.filter(|e| e.ibl) {
+            // Con entorno, el difuso lo traen los rebotes (al agotarlos, la irradiancia) y se suma su reflejo
+            Some(entorno) => {
+                let reflectance = pbr::env_brdf(albedo, material.metallic, material.roughness, normal.dot(view_dir).max(1e-4));
+                if depth >= self.max_bounces {
+                    color = color + (Vector3::one() - reflectance) * diffuse_albedo * entorno.irradiance(normal) / PI;
+                }
+                let reflected = (ray.direction - normal * (2.0 * ray.direction.dot(normal))).normalized();
+                let incoming = if depth < self.max_bounces && material.roughness < TRACED_REFLECTION_ROUGHNESS {
+                    self.radiance(&Ray::new(point + normal * 0.001, reflected), depth + 1, rng)
+                } else {
+                    entorno.sample_rough(reflected, material.roughness)
+                };
+                color = color + reflectance * incoming;
+            }
+            // Ambiente con el color de la primera luz (sólo la parte difusa, los metales no la reciben)
+            None => {
+                let ambient = self.lights.first().map_or(Vector3::zero(), light::color_vec) * material.ambient;
+                color = color + diffuse_albedo * ambient;
+            }
+        }
+
+        // Luz directa Cook-Torrance con un rayo de sombra hacia cada luz.
+        // El color de la luz es la irradiancia en incidencia normal, de ahí el factor PI
+        for light in self.lights {
+            let (light_dir, _, attenuation) = light.incidence(point);
+            if normal.dot(light_dir) > 0.0 && attenuation > 0.0
+                && !intersecto_ray::is_in_shadow(point, light.shadow_target(point), &self.shadow_triangles) {
+                let brdf = pbr::cook_torrance(normal, view_dir, light_dir, albedo, material.metallic, material.roughness);
+                color = color + brdf * light::color_vec(light) * (attenuation * PI);
+            }
+        }
+
+        // Rebote difuso (muestreo coseno, la BRDF lambertiana se cancela con la pdf)
+        if depth < self.max_bounces {
+            let bounce = Ray::new(point + normal * 0.001, cosine_hemisphere(normal, rng));
+            color = color + diffuse_albedo * self.radiance(&bounce, depth + 1, rng);
+        }
+        color
+    }
+
+    // Rayo que no choca con nada: el entorno siempre se ve de fondo, pero sólo ilumina con `ibl`
+    fn miss(&self, direction: Vector3, depth: u32) -> Vector3 {
+        match self.entorno {
+            Some(entorno) if depth == 0 || entorno.ibl => entorno.sample(direction),
+            _ => self.background,
+        }
+    }
+
+    // UV del punto (desplazada por el mapa de alturas) y normal de sombreado: la de los vértices
+    // interpolada con las baricéntricas, perturbada por el mapa de normales
+    fn surface(&self, tri: &Triangulo, u: f32, v: f32, view_dir: Vector3) -> (Vector2, Vector3) {
+        let w = 1.0 - u - v;
+        let mut uv = tri.uv[0] * w + tri.uv[1] * u + tri.uv[2] * v;
+        let material = &tri.material;
+        let smooth = tri.normals[0] * w + tri.normals[1] * u + tri.normals[2] * v;
+        // Normales opuestas entre vértices pueden anularse: queda la de la cara
+        let smooth = if smooth.length() > 1e-6 { smooth.normalized() } else { tri.normal };
+
+        if let Some(height_map) = material.height_map.and_then(|i| self.textures.get(i)) {
+            let view_ts = Vector3::new(view_dir.dot(tri.tangent), view_dir.dot(tri.bitangent), view_dir.dot(smooth));
+            uv = relieve::parallax_uv(uv, view_ts, material.parallax_scale, |p| texel(height_map, p).x);
+        }
+        let normal = match material.normal_map.and_then(|i| self.textures.get(i)) {
+            Some(normal_map) => relieve::perturb_normal(texel(normal_map, uv), tri.tangent, tri.bitangent,
+                                                        smooth, material.normal_strength),
+            None => smooth,
+        };
+        (uv, normal)
+    }
+
+    fn albedo(&self, tri: &Triangulo, uv: Vector2) -> Vector3 {
+        match tri.material.texture.and_then(|i| self.textures.get(i)) {
+            Some(tex) => tri.material.albedo * sample_texture(tex, uv),
+            None => tri.material.albedo,
+        }
+    }
+}
+
+// Triángulos de los objetos (con UVs) y del piso en espacio mundo
+fn collect_triangles(escena: &Escena) -> Vec<Triangulo> {
+    let mut triangles = Vec::new();
+
+    for objeto in &escena.objetos {
+        for TrianguloUV { normal, verts, texture } in objeto.textured_triangles() {
+            let (v, uv) = (verts.map(|vert| vert.pos), verts.map(|vert| vert.uv));
+            let (tangent, bitangent) = relieve::tangent_frame(v, uv, normal);
+            triangles.push(Triangulo {
+                v,
+                uv,
+                normal,
+                normals: verts.map(|vert| vert.normal),
+                tangent,
+                bitangent,
+                material: Material { texture: texture.or(objeto.material.texture), ..objeto.material },
+            });
+        }
+    }
+
+    // Piso con su material
+    let h = escena.piso.size / 2.0;
+    let y = escena.piso.y_position;
+    let corners = [
+        Vector3::new(-h, y, -h),
+        Vector3::new(h, y, -h),
+        Vector3::new(h, y, h),
+        Vector3::new(-h, y, h),
+    ];
+    let uvs = corners.map(|p| escena.piso.uv_at(p.x, p.z));
+    for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+        triangles.push(Triangulo {
+            v: [corners[a], corners[b], corners[c]],
+            uv: [uvs[a], uvs[b], uvs[c]],
+            normal: Vector3::new(0.0, 1.0, 0.0),
+            normals: [Vector3::new(0.0, 1.0, 0.0); 3],
+            tangent: Vector3::new(1.0, 0.0, 0.0),
+            bitangent: Vector3::new(0.0, 0.0, 1.0),
+            material: escena.piso.material,
+        });
+    }
+
+    triangles
+}
+
+// Rayo primario a través del píxel (px, py) con la misma proyección en perspectiva que Camera3D
+pub fn camera_ray(cam: &Camera3D, px: f32, py: f32, width: u32, height: u32) -> Ray {
+    let forward = (cam.target - cam.position).normalized();
+    let right = forward.cross(cam.up).normalized();
+    let up = right.cross(forward);
+    let tan_half = (cam.fovy.to_radians() * 0.5).tan();
+    let aspect = width as f32 / height as f32;
+
+    let x = (2.0 * px / width as f32 - 1.0) * aspect * tan_half;
+    let y = (1.0 - 2.0 * py / height as f32) * tan_half;
+    Ray::new(cam.position, forward + right * x + up * y)
+}
+
+fn cosine_hemisphere(normal: Vector3, rng: &mut Rng) -> Vector3 {
+    let r1 = rng.next_f32();
+    let r2 = rng.next_f32();
+    let phi = 2.0 * std::f32::consts::PI * r1;
+    let r = r2.sqrt();
+
+    let helper = if normal.x.abs() > 0.9 { Vector3::new(0.0, 1.0, 0.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+    let tangent = helper.cross(normal).normalized();
+    let bitangent = normal.cross(tangent);
+    (tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * (1.0 - r2).sqrt()).normalized()
+}
+
+// Muestreo nearest con repetición, mismas coordenadas UV que usa rlTexCoord2f.
+// Valores crudos en 0..1, como se leen los mapas de normales y de altura.
+// Las texturas nunca están vacías (archivo_malla::load_texture_image las rechaza)
+fn texel(tex: &RgbaImage, uv: Vector2) -> Vector3 {
+    let x = ((uv.x.rem_euclid(1.0) * tex.width() as f32) as u32).min(tex.width() - 1);
+    let y = ((uv.y.rem_euclid(1.0) * tex.height() as f32) as u32).min(tex.height() - 1);
+    let p = tex.get_pixel(x, y);
+    Vector3::new(p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0)
+}
+
+// Las texturas de color están en sRGB; se pasan a lineal para iluminar
+fn sample_texture(tex: &RgbaImage, uv: Vector2) -> Vector3 {
+    let c = texel(tex, uv);
+    Vector3::new(pbr::srgb_to_linear(c.x), pbr::srgb_to_linear(c.y), pbr::srgb_to_linear(c.z))
+}
+
+// Generador xorshift simple para no depender de crates externos
+struct Rng(u32);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+// Módulo para el trazador de rayos por CPU
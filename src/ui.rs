@@ -1,25 +1,97 @@
 use raylib::prelude::*;
-use crate::controles::{CameraControls, LightControls};
+use crate::controles::{CameraControls, CameraMode, LightControls};
+use crate::escena::RenderMode;
+use crate::sombras::ShadowSettings;
+use crate::tonos::Tonemap;
+use crate::posproceso::PostSettings;
+use crate::entrada::{Action, InputMap};
 
-pub fn render_ui(d: &mut RaylibDrawHandle, camera_controls: &CameraControls, light_controls: &LightControls) {
+// Ayuda de controles: cada `{}` se reemplaza por las entradas de la acción que le toca,
+// así el texto sigue al archivo de controles
+const HELP: &[(&str, &[Action])] = &[
+    ("{}, {}, {}, {}: Rotar cámara", &[Action::OrbitLeft, Action::OrbitRight, Action::OrbitUp, Action::OrbitDown]),
+    ("{}, {}: Zoom in/out (también la rueda)", &[Action::ZoomIn, Action::ZoomOut]),
+    ("{} + arrastrar: rotar, {} + arrastrar: mover centro", &[Action::Rotate, Action::Pan]),
+    ("{}: Órbita / vuelo libre / primera persona, {}: Ocultar menú", &[Action::CycleCameraMode, Action::ToggleUi]),
+    ("{}, {}, {}, {}, {}, {}: Moverse en vuelo libre", &[Action::MoveForward, Action::MoveLeft, Action::MoveBack,
+                                                        Action::MoveRight, Action::MoveDown, Action::MoveUp]),
+    ("{}, {}, {}, {}: Girar la mirada (vuelo libre y primera persona)", &[Action::LookLeft, Action::LookRight, Action::LookUp, Action::LookDown]),
+    ("{}: Mostrar normales, {}: Alternar bordes", &[Action::ToggleNormals, Action::ToggleWireframe]),
+    ("{}, {}: Intensidad luz ({}: girarla)", &[Action::IntensityUp, Action::IntensityDown, Action::Modifier]),
+    ("{}: Pausar/reanudar rotación luz", &[Action::ToggleLightRotation]),
+    ("{}: Shader / PBR / CPU por cara / Trazado", &[Action::CycleRenderMode]),
+    ("{}, {}: Suavizado de sombras (PCF; con {}: resolución)", &[Action::PcfDown, Action::PcfUp, Action::Modifier]),
+    ("{}: Cambiar luz, {}: Elegir y arrastrar una luz, {}: Gizmo", &[Action::NextLight, Action::Grab, Action::ToggleGizmo]),
+    ("{}, {}: Tono de la luz, {}: Luz blanca", &[Action::HueLeft, Action::HueRight, Action::WhiteLight]),
+    ("{}: Operador de tono, {}, {}: Exposición", &[Action::CycleTonemapper, Action::ExposureDown, Action::ExposureUp]),
+    ("{}, {}, {}, {}, {}, {}: Bloom, prof. de campo, FXAA, LUT, aberración, viñeta",
+     &[Action::ToggleBloom, Action::ToggleDepthOfField, Action::ToggleFxaa,
+       Action::ToggleColorGrading, Action::ToggleChromaticAberration, Action::ToggleVignette]),
+    ("{}: Captura PNG ({}: EXR), {}: Grabar secuencia", &[Action::Screenshot, Action::Modifier, Action::ToggleRecording]),
+];
+
+// Rellena una línea de HELP; una acción sin entradas se muestra como "-"
+fn help_line(template: &str, actions: &[Action], input: &InputMap) -> String {
+    let mut parts = template.split("{}");
+    let mut line = parts.next().unwrap_or("").to_string();
+    for (part, action) in parts.zip(actions) {
+        let keys = input.describe(*action);
+        line.push_str(if keys.is_empty() { "-" } else { &keys });
+        line.push_str(part);
+    }
+    line
+}
+
+// Estado del render que muestra el menú, armado en cada fotograma
+pub struct HudInfo<'a> {
+    pub render_mode: RenderMode,
+    pub shadows: &'a ShadowSettings,
+    pub tonemap: &'a Tonemap,
+    pub post: &'a PostSettings,
+    pub input: &'a InputMap,
+}
+
+pub fn render_ui(d: &mut RaylibDrawHandle, camera_controls: &CameraControls, light_controls: &LightControls,
+                 hud: &HudInfo) {
+    let HudInfo { render_mode, shadows, tonemap, post, input } = *hud;
     // Información en pantalla
     d.draw_fps(10, 10);
     d.draw_text("Controles:", 10, 40, 20, Color::WHITE);
-    d.draw_text("Flechas/WASD: Rotar cámara", 10, 65, 16, Color::LIGHTGRAY);
-    d.draw_text("Q/E: Zoom in/out", 10, 85, 16, Color::LIGHTGRAY);
-    d.draw_text("Mouse: Click + drag para rotar", 10, 105, 16, Color::LIGHTGRAY);
-    d.draw_text("Rueda: Zoom", 10, 125, 16, Color::LIGHTGRAY);
-    d.draw_text("N: Mostrar normales", 10, 145, 16, Color::LIGHTGRAY);
-    d.draw_text("B: Alternar bordes", 10, 165, 16, Color::LIGHTGRAY);
-    d.draw_text("+/-: Intensidad luz", 10, 185, 16, Color::LIGHTGRAY);
-    d.draw_text("L: Pausar/reanudar rotación luz", 10, 205, 16, Color::LIGHTGRAY);
+    for (i, (template, actions)) in HELP.iter().enumerate() {
+        d.draw_text(&help_line(template, actions, input), 10, 65 + 20 * i as i32, 16, Color::LIGHTGRAY);
+    }
     
     // Información de posición
-    d.draw_text(&format!("Radius: {:.2}", camera_controls.radius), 10, 225, 16, Color::YELLOW);
-    d.draw_text(&format!("Yaw: {:.2}°", camera_controls.yaw.to_degrees()), 10, 245, 16, Color::YELLOW);
-    d.draw_text(&format!("Pitch: {:.2}°", camera_controls.pitch.to_degrees()), 10, 265, 16, Color::YELLOW);
+    if camera_controls.mode == CameraMode::Orbit {
+        d.draw_text(&format!("Cámara: {}, radius {:.2}", camera_controls.mode.label(), camera_controls.radius), 10, 385, 16, Color::YELLOW);
+        d.draw_text(&format!("Yaw: {:.2}°", camera_controls.yaw.to_degrees()), 10, 405, 16, Color::YELLOW);
+        d.draw_text(&format!("Pitch: {:.2}°", camera_controls.pitch.to_degrees()), 10, 425, 16, Color::YELLOW);
+    } else {
+        let p = camera_controls.position;
+        d.draw_text(&format!("Cámara: {} ({:.1}, {:.1}, {:.1})", camera_controls.mode.label(), p.x, p.y, p.z), 10, 385, 16, Color::YELLOW);
+        d.draw_text(&format!("Yaw: {:.2}°", camera_controls.look_yaw.to_degrees()), 10, 405, 16, Color::YELLOW);
+        d.draw_text(&format!("Pitch: {:.2}°", camera_controls.look_pitch.to_degrees()), 10, 425, 16, Color::YELLOW);
+    }
     
-    // Estado de la luz
-    let light_status = if light_controls.auto_rotate { "Rotando" } else { "Estática" };
-    d.draw_text(&format!("Luz: {} (Int: {:.1})", light_status, light_controls.intensity), 10, 285, 16, Color::CYAN);
+    // Estado de la luz seleccionada
+    if let Some(orbit) = light_controls.selected() {
+        let light_status = if orbit.auto_rotate { "Rotando" } else { "Estática" };
+        d.draw_text(&format!("Luz {}/{} {}: {} (Int: {:.1})", light_controls.selected + 1, light_controls.orbits.len(),
+                             orbit.kind.label(), light_status, orbit.intensity), 10, 445, 16, Color::CYAN);
+        // Muestra del color de la luz
+        let [r, g, b] = orbit.color.map(|c| (c * 255.0).clamp(0.0, 255.0) as u8);
+        d.draw_rectangle(10, 465, 40, 14, Color::new(r, g, b, 255));
+        d.draw_rectangle_lines(10, 465, 40, 14, Color::WHITE);
+        d.draw_text(&format!("Radio {:.2}, altura {:.2}", orbit.radius, orbit.height), 60, 464, 16, Color::CYAN);
+    }
+    d.draw_text(&format!("Render: {}", render_mode.label()), 10, 485, 16, Color::CYAN);
+    d.draw_text(&format!("Tono: {}, exposición {:+.1} EV", tonemap.tonemapper.label(), tonemap.exposure), 10, 505, 16, Color::CYAN);
+    // Efectos activos en el orden en que se aplican
+    let (hdr, display) = post.active();
+    let efectos: Vec<&str> = hdr.iter().chain(&display).map(|e| e.label()).collect();
+    let efectos = if efectos.is_empty() { "ninguno".to_string() } else { efectos.join(" > ") };
+    d.draw_text(&format!("Post: {}", efectos), 10, 525, 16, Color::CYAN);
+    if render_mode.uses_shader() {
+        d.draw_text(&format!("Shadow map: {}px, PCF {}", shadows.resolution, shadows.pcf_radius), 10, 545, 16, Color::CYAN);
+    }
 }